@@ -0,0 +1,223 @@
+use near_sdk::json_types::{Base58CryptoHash, U64};
+use near_sdk::CryptoHash;
+
+use crate::*;
+
+pub type AirdropRoundId = u64;
+
+/// Number of leaves tracked by one word of the claimed-bitmap.
+const BITMAP_WORD_BITS: u64 = 128;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum AirdropStatus {
+    Active,
+    Expired,
+    Reclaimed,
+}
+
+/// A distribution of PLAT committed to by the Merkle root of its `(index, account, amount)` leaves.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AirdropRound {
+    pub id: AirdropRoundId,
+    pub merkle_root: Base58CryptoHash,
+    /// Amount deposited by the owner for the round.
+    pub total: U128,
+    pub claimed_amount: U128,
+    pub claimed_count: u64,
+    /// Timestamp (in nanoseconds) after which nothing can be claimed and the owner can reclaim the leftovers.
+    pub expires_at: U64,
+    pub status: AirdropStatus,
+}
+
+/// Hash of a leaf of the airdrop tree: `sha256(borsh(index, account_id, amount))`.
+pub fn airdrop_leaf_hash(index: u64, account_id: &AccountId, amount: Balance) -> CryptoHash {
+    let leaf = (index, account_id.clone(), amount).try_to_vec().unwrap();
+    env::sha256_array(&leaf)
+}
+
+/// Recomputes the root from a leaf and its proof. The bits of `index` tell at each level
+/// whether the current node is the left (0) or the right (1) child.
+pub fn airdrop_root_from_proof(
+    leaf: CryptoHash,
+    index: u64,
+    proof: &[Base58CryptoHash],
+) -> CryptoHash {
+    let mut node = leaf;
+    let mut position = index;
+    for sibling in proof {
+        let sibling: CryptoHash = (*sibling).into();
+        let mut pair = Vec::with_capacity(64);
+        if position % 2 == 0 {
+            pair.extend_from_slice(&node);
+            pair.extend_from_slice(&sibling);
+        } else {
+            pair.extend_from_slice(&sibling);
+            pair.extend_from_slice(&node);
+        }
+        node = env::sha256_array(&pair);
+        position /= 2;
+    }
+    node
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Opens an airdrop round funded with `total` tokens from the owner's balance.
+    /// The attached deposit covers the storage of the round, the rest is refunded.
+    #[payable]
+    pub fn create_airdrop(
+        &mut self,
+        merkle_root: Base58CryptoHash,
+        total: U128,
+        expires_at: U64,
+    ) -> AirdropRoundId {
+        self.assert_owner();
+        let init_storage = env::storage_usage();
+        require!(total.0 > 0, "The amount should be a positive number");
+        require!(
+            expires_at.0 > env::block_timestamp(),
+            "The expiry should be in the future"
+        );
+
+        let id = self.next_airdrop_id;
+        self.next_airdrop_id += 1;
+        let round = AirdropRound {
+            id,
+            merkle_root,
+            total,
+            claimed_amount: U128(0),
+            claimed_count: 0,
+            expires_at,
+            status: AirdropStatus::Active,
+        };
+        self.airdrops.insert(&id, &round);
+
+        let owner_id = self.owner_id.clone();
+        self.internal_lock(&owner_id, total.0, Some(format!("Airdrop round {}", id)));
+
+        refund_deposit(init_storage);
+        id
+    }
+
+    /// Claims the caller's leaf `index` of the round. The caller must be registered.
+    /// Returns the claimed amount.
+    pub fn claim_airdrop(
+        &mut self,
+        round_id: AirdropRoundId,
+        index: u64,
+        amount: U128,
+        proof: Vec<Base58CryptoHash>,
+    ) -> U128 {
+        let account_id = env::predecessor_account_id();
+        let mut round = self.internal_unwrap_airdrop(round_id);
+        require!(
+            round.status == AirdropStatus::Active && env::block_timestamp() < round.expires_at.0,
+            "The airdrop round is over"
+        );
+        require!(
+            !self.internal_is_airdrop_claimed(round_id, index),
+            "The airdrop has already been claimed"
+        );
+
+        let leaf = airdrop_leaf_hash(index, &account_id, amount.0);
+        let root: CryptoHash = round.merkle_root.into();
+        require!(
+            airdrop_root_from_proof(leaf, index, &proof) == root,
+            "Invalid merkle proof"
+        );
+
+        let claimed_amount = round.claimed_amount.0 + amount.0;
+        require!(
+            claimed_amount <= round.total.0,
+            "The airdrop round doesn't have enough tokens left"
+        );
+        round.claimed_amount = U128(claimed_amount);
+        round.claimed_count += 1;
+        self.airdrops.insert(&round_id, &round);
+        self.internal_set_airdrop_claimed(round_id, index);
+        self.airdrop_claimers
+            .insert(&(round_id, account_id.clone()));
+
+        self.internal_unlock(
+            &account_id,
+            amount.0,
+            Some(format!("Airdrop round {}", round_id)),
+        );
+        amount
+    }
+
+    /// Returns the unclaimed tokens of an expired round to the owner.
+    pub fn reclaim_airdrop(&mut self, round_id: AirdropRoundId) -> U128 {
+        self.assert_owner();
+        let mut round = self.internal_unwrap_airdrop(round_id);
+        require!(
+            round.status == AirdropStatus::Active,
+            "The airdrop round has already been reclaimed"
+        );
+        require!(
+            env::block_timestamp() >= round.expires_at.0,
+            "The airdrop round has not expired yet"
+        );
+
+        let leftover = round.total.0 - round.claimed_amount.0;
+        round.status = AirdropStatus::Reclaimed;
+        self.airdrops.insert(&round_id, &round);
+
+        if leftover > 0 {
+            let owner_id = self.owner_id.clone();
+            self.internal_unlock(
+                &owner_id,
+                leftover,
+                Some(format!("Airdrop round {} reclaimed", round_id)),
+            );
+        }
+        leftover.into()
+    }
+
+    pub fn get_airdrop(&self, round_id: AirdropRoundId) -> Option<AirdropRound> {
+        self.airdrops.get(&round_id).map(|mut round| {
+            if round.status == AirdropStatus::Active
+                && env::block_timestamp() >= round.expires_at.0
+            {
+                round.status = AirdropStatus::Expired;
+            }
+            round
+        })
+    }
+
+    /// Whether the leaf `index` of the round has been claimed. Leaf indexes are assigned
+    /// to accounts in the distribution list the root was built from.
+    pub fn is_airdrop_claimed(&self, round_id: AirdropRoundId, index: u64) -> bool {
+        self.internal_is_airdrop_claimed(round_id, index)
+    }
+
+    /// Whether the account has claimed its airdrop of the round.
+    pub fn has_claimed_airdrop(&self, round_id: AirdropRoundId, account_id: AccountId) -> bool {
+        self.airdrop_claimers.contains(&(round_id, account_id))
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_unwrap_airdrop(&self, round_id: AirdropRoundId) -> AirdropRound {
+        self.airdrops
+            .get(&round_id)
+            .unwrap_or_else(|| env::panic_str("Airdrop round not found"))
+    }
+
+    pub(crate) fn internal_is_airdrop_claimed(&self, round_id: AirdropRoundId, index: u64) -> bool {
+        let word = self
+            .airdrop_claimed
+            .get(&(round_id, index / BITMAP_WORD_BITS))
+            .unwrap_or(0);
+        word & (1u128 << (index % BITMAP_WORD_BITS)) != 0
+    }
+
+    pub(crate) fn internal_set_airdrop_claimed(&mut self, round_id: AirdropRoundId, index: u64) {
+        let key = (round_id, index / BITMAP_WORD_BITS);
+        let word = self.airdrop_claimed.get(&key).unwrap_or(0);
+        self.airdrop_claimed
+            .insert(&key, &(word | (1u128 << (index % BITMAP_WORD_BITS))));
+    }
+}
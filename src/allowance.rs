@@ -0,0 +1,73 @@
+use near_sdk::{assert_one_yocto, Promise};
+
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Allows `spender_id` to transfer up to `amount` of the caller's tokens with `ft_transfer_from`.
+    /// Replaces any previous allowance. The caller pays for the storage of a new allowance,
+    /// the rest of the attached deposit is refunded.
+    #[payable]
+    pub fn ft_approve(&mut self, spender_id: AccountId, amount: U128) {
+        let init_storage = env::storage_usage();
+        let owner_id = env::predecessor_account_id();
+        require!(owner_id != spender_id, "Owner and spender should be different");
+        // Only registered accounts hold tokens that can be approved
+        self.internal_unwrap_balance_of(&owner_id);
+
+        self.allowances.insert(&(owner_id, spender_id), &amount.0);
+        refund_deposit(init_storage);
+    }
+
+    /// Removes the allowance given to `spender_id` and refunds its storage to the caller.
+    #[payable]
+    pub fn ft_revoke(&mut self, spender_id: AccountId) {
+        assert_one_yocto();
+        let init_storage = env::storage_usage();
+        let owner_id = env::predecessor_account_id();
+        if self
+            .allowances
+            .remove(&(owner_id.clone(), spender_id))
+            .is_none()
+        {
+            env::panic_str("No allowance to revoke");
+        }
+        let released = Balance::from(init_storage - env::storage_usage()) * env::storage_byte_cost();
+        if released > 0 {
+            Promise::new(owner_id).transfer(released);
+        }
+    }
+
+    /// Transfers `amount` of `owner_id`'s tokens to `receiver_id` on behalf of the caller,
+    /// spending the allowance the owner gave to the caller.
+    #[payable]
+    pub fn ft_transfer_from(
+        &mut self,
+        owner_id: AccountId,
+        receiver_id: AccountId,
+        amount: U128,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        let spender_id = env::predecessor_account_id();
+        let amount: Balance = amount.into();
+        let key = (owner_id.clone(), spender_id);
+
+        let allowance = self.allowances.get(&key).unwrap_or(0);
+        if let Some(new_allowance) = allowance.checked_sub(amount) {
+            self.allowances.insert(&key, &new_allowance);
+        } else {
+            env::panic_str("The transfer exceeds the allowance");
+        }
+
+        self.internal_transfer(&owner_id, &receiver_id, amount, memo);
+    }
+
+    /// Returns how many of `owner_id`'s tokens `spender_id` can still transfer.
+    pub fn ft_allowance(&self, owner_id: AccountId, spender_id: AccountId) -> U128 {
+        self.allowances
+            .get(&(owner_id, spender_id))
+            .unwrap_or(0)
+            .into()
+    }
+}
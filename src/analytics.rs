@@ -0,0 +1,218 @@
+use crate::*;
+
+/// Platform-wide aggregates, kept up to date with every change of the events.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PlatformStats {
+    pub events_pending: u64,
+    pub events_active: u64,
+    pub events_finished: u64,
+    pub events_cancelled: u64,
+    /// Accounts that have sponsored at least one event
+    pub unique_sponsors: u64,
+    /// Sum of the totals of every event
+    pub total_near: U128,
+    pub total_usdt: U128,
+}
+
+impl Default for PlatformStats {
+    fn default() -> Self {
+        Self {
+            events_pending: 0,
+            events_active: 0,
+            events_finished: 0,
+            events_cancelled: 0,
+            unique_sponsors: 0,
+            total_near: U128(0),
+            total_usdt: U128(0),
+        }
+    }
+}
+
+impl PlatformStats {
+    fn status_count(&mut self, status: &Status) -> &mut u64 {
+        match status {
+            Status::Pending => &mut self.events_pending,
+            Status::Active => &mut self.events_active,
+            Status::Finish => &mut self.events_finished,
+            Status::Cancel => &mut self.events_cancelled,
+        }
+    }
+}
+
+/// Aggregates of the events of an owner.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct OwnerStats {
+    pub events: u64,
+    pub total_near: U128,
+    pub total_usdt: U128,
+}
+
+impl Default for OwnerStats {
+    fn default() -> Self {
+        Self {
+            events: 0,
+            total_near: U128(0),
+            total_usdt: U128(0),
+        }
+    }
+}
+
+/// Contributions received by an event.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Default, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventActivity {
+    /// Sponsorships and top-ups
+    pub contributions: u64,
+    /// Timestamp (in nanoseconds) of the last of them
+    pub last_contribution_at: Option<U64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventStats {
+    pub sponsors: u64,
+    pub contributions: u64,
+    pub total_near: U128,
+    pub total_usdt: U128,
+    /// Average sponsorship of the current sponsors in each token
+    pub average_near: U128,
+    pub average_usdt: U128,
+    pub last_contribution_at: Option<U64>,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_platform_stats(&self) -> PlatformStats {
+        self.platform_stats.clone()
+    }
+
+    pub fn get_owner_stats(&self, account_id: AccountId) -> OwnerStats {
+        self.owner_stats.get(&account_id).unwrap_or_default()
+    }
+
+    pub fn get_event_stats(&self, event_id: EventId) -> EventStats {
+        let event = self.internal_watch_detail_event(&event_id);
+        let activity = self.event_activity.get(&event_id).unwrap_or_default();
+        let sponsors = event.sponsers.len() as u128;
+        let average = |total: Balance| total.checked_div(sponsors).unwrap_or(0);
+        EventStats {
+            sponsors: sponsors as u64,
+            contributions: activity.contributions,
+            total_near: event.total_near.into(),
+            total_usdt: event.total_usdt.into(),
+            average_near: average(event.total_near).into(),
+            average_usdt: average(event.total_usdt).into(),
+            last_contribution_at: activity.last_contribution_at,
+        }
+    }
+
+    /// Returns the events with the largest totals in `token`, from the largest.
+    pub fn get_largest_events(
+        &self,
+        token: Token,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(EventId, U128)> {
+        let ranking = match token {
+            Token::NEAR => &self.largest_events_near,
+            Token::USDT => &self.largest_events_usdt,
+            Token::PLAT => env::panic_str("Events are sponsored in NEAR or USDT"),
+        };
+        ranking
+            .iter_rev()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(10) as usize)
+            .map(|((total, event_id), _)| (event_id, U128(total)))
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Updates the aggregates after the total of the event in `token` changed from `old_total`.
+    pub(crate) fn internal_on_total_change(
+        &mut self,
+        event: &Event,
+        token: &Token,
+        old_total: Balance,
+    ) {
+        let (new_total, ranking) = match token {
+            Token::NEAR => (event.total_near, &mut self.largest_events_near),
+            _ => (event.total_usdt, &mut self.largest_events_usdt),
+        };
+        if old_total == new_total {
+            return;
+        }
+        if old_total > 0 {
+            ranking.remove(&(old_total, event.id.clone()));
+        }
+        if new_total > 0 {
+            ranking.insert(&(new_total, event.id.clone()), &());
+        }
+
+        let mut owner_stats = self.owner_stats.get(&event.owner).unwrap_or_default();
+        let (platform_total, owner_total) = match token {
+            Token::NEAR => (
+                &mut self.platform_stats.total_near,
+                &mut owner_stats.total_near,
+            ),
+            _ => (
+                &mut self.platform_stats.total_usdt,
+                &mut owner_stats.total_usdt,
+            ),
+        };
+        for total in [platform_total, owner_total] {
+            *total = U128(total.0.saturating_sub(old_total).saturating_add(new_total));
+        }
+        self.owner_stats.insert(&event.owner, &owner_stats);
+    }
+
+    /// Moves the event to `status` and updates the event counts.
+    pub(crate) fn internal_set_event_status(&mut self, event: &mut Event, status: Status) {
+        let old_count = self.platform_stats.status_count(&event.status);
+        *old_count = old_count.saturating_sub(1);
+        *self.platform_stats.status_count(&status) += 1;
+        event.status = status;
+    }
+
+    pub(crate) fn internal_track_new_event(&mut self, event: &Event) {
+        *self.platform_stats.status_count(&event.status) += 1;
+        let mut owner_stats = self.owner_stats.get(&event.owner).unwrap_or_default();
+        owner_stats.events += 1;
+        self.owner_stats.insert(&event.owner, &owner_stats);
+    }
+
+    /// Moves the event and its totals to the stats of its new owner.
+    pub(crate) fn internal_track_owner_change(&mut self, event: &Event, old_owner_id: &AccountId) {
+        let mut old_stats = self.owner_stats.get(old_owner_id).unwrap_or_default();
+        old_stats.events = old_stats.events.saturating_sub(1);
+        old_stats.total_near = U128(old_stats.total_near.0.saturating_sub(event.total_near));
+        old_stats.total_usdt = U128(old_stats.total_usdt.0.saturating_sub(event.total_usdt));
+        self.owner_stats.insert(old_owner_id, &old_stats);
+
+        let mut new_stats = self.owner_stats.get(&event.owner).unwrap_or_default();
+        new_stats.events += 1;
+        new_stats.total_near = U128(new_stats.total_near.0 + event.total_near);
+        new_stats.total_usdt = U128(new_stats.total_usdt.0 + event.total_usdt);
+        self.owner_stats.insert(&event.owner, &new_stats);
+    }
+
+    pub(crate) fn internal_track_sponsor(&mut self, sponser_id: &AccountId) {
+        if self.known_sponsors.insert(sponser_id) {
+            self.platform_stats.unique_sponsors += 1;
+        }
+    }
+
+    pub(crate) fn internal_track_contribution(
+        &mut self,
+        sponser_id: &AccountId,
+        event_id: &EventId,
+    ) {
+        self.internal_track_sponsor(sponser_id);
+        let mut activity = self.event_activity.get(event_id).unwrap_or_default();
+        activity.contributions += 1;
+        activity.last_contribution_at = Some(env::block_timestamp().into());
+        self.event_activity.insert(event_id, &activity);
+    }
+}
@@ -24,6 +24,7 @@ impl Contract {
                             .filter(|item| *item != receiver_id)
                             .collect();
                         self.events.insert(&event_id, &res);
+                        self.internal_on_total_change(&res, &Token::NEAR, res.total_near + amount);
                     }
                     None => env::panic_str("EventId is not Found"),
                 }
@@ -55,6 +56,7 @@ impl Contract {
                             .filter(|item| *item != receiver_id)
                             .collect();
                         self.events.insert(&event_id, &res);
+                        self.internal_on_total_change(&res, &Token::USDT, res.total_usdt + amount);
                     }
                     None => env::panic_str("EventId is not Found"),
                 }
@@ -66,6 +68,7 @@ impl Contract {
     }
 
     pub(crate) fn handle_sponser_claim(&mut self, sponser_id: AccountId, event_id: EventId) {
+        let old_amount = self.internal_unwrap_balance(&sponser_id, &event_id);
         match self.sponser_to_sponse.get(&sponser_id) {
             Some(mut res) => {
                 if res.events.len() == 1 {
@@ -78,6 +81,9 @@ impl Contract {
             }
             None => env::panic_str("You haven't sponse this event before"),
         }
+        if let Ok(old_amount) = old_amount {
+            self.internal_update_leaderboards(&sponser_id, &event_id, &old_amount);
+        }
     }
 
     pub fn storage_deposit_callback_add_token(&mut self) {
@@ -94,4 +100,214 @@ impl Contract {
             }
         }
     }
+
+    #[private]
+    pub fn task_payout_callback(&mut self, task_id: TaskId, receiver_id: AccountId, amount: U128) {
+        assert_eq!(env::promise_results_count(), 1, "ERR_TOO_MANY_RESULTS");
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {}
+            PromiseResult::Failed => {
+                // give the reward back to the task so the account can claim it again
+                let mut task_info = self.internal_unwrap_task(&task_id);
+                task_info.amount = U128(task_info.amount.0 + amount.0);
+                task_info.participants -= 1;
+                self.tasks.insert(&task_id, &task_info);
+                self.task_participants.remove(&(task_id, receiver_id));
+            }
+        }
+    }
+
+    #[private]
+    pub fn task_refund_callback(&mut self, task_id: TaskId, amount: U128) {
+        assert_eq!(env::promise_results_count(), 1, "ERR_TOO_MANY_RESULTS");
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {}
+            PromiseResult::Failed => {
+                // put the budget back so that the task can be closed again
+                let mut task_info = self.internal_unwrap_task(&task_id);
+                task_info.amount = amount;
+                task_info.status = TaskStatus::Active;
+                self.tasks.insert(&task_id, &task_info);
+            }
+        }
+    }
+
+    #[private]
+    pub fn raffle_payout_callback(
+        &mut self,
+        raffle_id: RaffleId,
+        receiver_id: AccountId,
+        amount: U128,
+    ) {
+        assert_eq!(env::promise_results_count(), 1, "ERR_TOO_MANY_RESULTS");
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {}
+            PromiseResult::Failed => {
+                // keep the payout so that it can be sent again
+                let mut raffle = self.internal_unwrap_raffle(raffle_id);
+                match raffle
+                    .unpaid
+                    .iter_mut()
+                    .find(|(unpaid_id, _)| *unpaid_id == receiver_id)
+                {
+                    Some((_, unpaid)) => *unpaid = U128(unpaid.0 + amount.0),
+                    None => raffle.unpaid.push((receiver_id, amount)),
+                }
+                self.raffles.insert(&raffle_id, &raffle);
+            }
+        }
+    }
+
+    #[private]
+    pub fn prize_payout_callback(&mut self, event_id: EventId, index: u64) {
+        assert_eq!(env::promise_results_count(), 1, "ERR_TOO_MANY_RESULTS");
+        let mut payout = self.internal_unwrap_prize_payout(&event_id, index);
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {
+                payout.status = PayoutStatus::Paid;
+                let mut distribution = self.internal_unwrap_prize_distribution(&event_id);
+                if payout.token == Token::NEAR {
+                    distribution.paid_near = U128(distribution.paid_near.0 + payout.amount.0);
+                } else {
+                    distribution.paid_usdt = U128(distribution.paid_usdt.0 + payout.amount.0);
+                }
+                self.prize_distributions.insert(&event_id, &distribution);
+            }
+            PromiseResult::Failed => {
+                // keep the prize in the contract so that the payout can be retried
+                payout.status = PayoutStatus::Failed;
+            }
+        }
+        self.prize_payouts.insert(&(event_id, index), &payout);
+    }
+
+    #[private]
+    pub fn milestone_release_callback(&mut self, event_id: EventId, token: Token, amount: U128) {
+        assert_eq!(env::promise_results_count(), 1, "ERR_TOO_MANY_RESULTS");
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {}
+            PromiseResult::Failed => {
+                // keep the tranche so that the owner can withdraw it again
+                let mut escrow = self.internal_unwrap_milestone_escrow(&event_id);
+                if token == Token::NEAR {
+                    escrow.unpaid_near = U128(escrow.unpaid_near.0 + amount.0);
+                } else {
+                    escrow.unpaid_usdt = U128(escrow.unpaid_usdt.0 + amount.0);
+                }
+                self.milestone_escrows.insert(&event_id, &escrow);
+            }
+        }
+    }
+
+    #[private]
+    pub fn ruling_payout_callback(&mut self, event_id: EventId, token: Token, amount: U128) {
+        assert_eq!(env::promise_results_count(), 1, "ERR_TOO_MANY_RESULTS");
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {}
+            PromiseResult::Failed => {
+                // keep the owner's share so that it can be withdrawn again
+                let mut unpaid = self.unpaid_rulings.get(&event_id).unwrap_or(Amount {
+                    token_near: 0,
+                    token_usdt: 0,
+                });
+                if token == Token::NEAR {
+                    unpaid.token_near += amount.0;
+                } else {
+                    unpaid.token_usdt += amount.0;
+                }
+                self.unpaid_rulings.insert(&event_id, &unpaid);
+            }
+        }
+    }
+
+    #[private]
+    pub fn withdraw_sponsorship_callback(
+        &mut self,
+        event_id: EventId,
+        sponser_id: AccountId,
+        token: Token,
+        amount: U128,
+        penalty: U128,
+        sponsored_at: U64,
+    ) {
+        assert_eq!(env::promise_results_count(), 1, "ERR_TOO_MANY_RESULTS");
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {
+                self.internal_collect_withdrawal_penalty(&event_id, &token, penalty.0);
+            }
+            PromiseResult::Failed => {
+                // the withdrawal is undone, the sponsor can withdraw or claim it again later
+                self.internal_cancel_withdrawal(
+                    &sponser_id,
+                    &event_id,
+                    &token,
+                    amount.0,
+                    penalty.0,
+                    sponsored_at.0,
+                );
+            }
+        }
+    }
+
+    #[private]
+    pub fn withdraw_fees_callback(&mut self, token: Token, amount: U128) {
+        assert_eq!(env::promise_results_count(), 1, "ERR_TOO_MANY_RESULTS");
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {}
+            PromiseResult::Failed => {
+                // the fees stay in the treasury so that they can be withdrawn again
+                self.internal_credit_treasury(&token, amount.0);
+            }
+        }
+    }
+
+    #[private]
+    pub fn sweep_unclaimed_callback(&mut self, token: Token, amount: U128) {
+        assert_eq!(env::promise_results_count(), 1, "ERR_TOO_MANY_RESULTS");
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {}
+            PromiseResult::Failed => {
+                // the refunds stay with the treasury so that they can be withdrawn with the fees
+                self.internal_credit_treasury(&token, amount.0);
+            }
+        }
+    }
+
+    #[private]
+    pub fn fee_refund_callback(&mut self, event_id: EventId, token: Token, amount: U128) {
+        assert_eq!(env::promise_results_count(), 1, "ERR_TOO_MANY_RESULTS");
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(_) => {}
+            PromiseResult::Failed => {
+                self.internal_keep_refused_fees(&event_id, &token, amount.0);
+            }
+        }
+    }
+
+    #[private]
+    pub fn price_data_callback(&mut self) {
+        assert_eq!(env::promise_results_count(), 1, "ERR_TOO_MANY_RESULTS");
+        match env::promise_result(0) {
+            PromiseResult::NotReady => unreachable!(),
+            PromiseResult::Successful(value) => {
+                match near_sdk::serde_json::from_slice::<PriceData>(&value) {
+                    Ok(price_data) => self.internal_cache_prices(price_data),
+                    Err(_) => env::panic_str("The price oracle returned invalid data"),
+                }
+            }
+            PromiseResult::Failed => {
+                env::panic_str("The price oracle call failed");
+            }
+        }
+    }
 }
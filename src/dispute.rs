@@ -0,0 +1,289 @@
+use crate::external::ext_self;
+use crate::prize::BPS_DENOMINATOR;
+use crate::*;
+
+/// Share of the sponsored amount, in basis points, that must vote for a cancellation in each token.
+pub const CANCELLATION_QUORUM_BPS: u16 = 5_000;
+/// How long sponsors have to reach the quorum once a cancellation vote is opened: 7 days.
+pub const CANCELLATION_VOTING_PERIOD: u64 = 7 * 24 * 60 * 60 * 1_000_000_000;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CancellationVote {
+    /// Number of votes opened on the event so far, this one included
+    pub round: u32,
+    /// Timestamp (in nanoseconds) after which the vote can no longer pass
+    pub deadline: U64,
+    pub quorum_bps: u16,
+    pub votes_near: U128,
+    pub votes_usdt: U128,
+    pub voters: u64,
+    pub passed: bool,
+}
+
+/// Decision of the arbitrator of an event on its funds.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Ruling {
+    /// The event is cancelled and sponsors claim back everything
+    Refund,
+    /// The event is finished and its funds go to the owner
+    Release,
+    /// The owner receives `owner_bps` of the funds, sponsors claim back the rest
+    Split { owner_bps: u16 },
+}
+
+impl Ruling {
+    /// Share of the funds going to the owner, in basis points.
+    pub fn owner_bps(&self) -> u16 {
+        match self {
+            Ruling::Refund => 0,
+            Ruling::Release => BPS_DENOMINATOR as u16,
+            Ruling::Split { owner_bps } => *owner_bps,
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the account that can settle disputes over the funds of the event. It can only be
+    /// changed before the first sponsorship, so that sponsors know who will rule on their funds.
+    pub fn set_event_arbitrator(&mut self, event_id: EventId, arbitrator_id: Option<AccountId>) {
+        let event = self.internal_watch_detail_event(&event_id);
+        require!(
+            self.check_owner_event(&event_id),
+            "Only the owner of the event can set its arbitrator"
+        );
+        require!(
+            event.sponsers.is_empty(),
+            "The arbitrator can't be changed once the event is sponsored"
+        );
+        match arbitrator_id {
+            Some(arbitrator_id) => {
+                require!(
+                    arbitrator_id != event.owner,
+                    "The owner can't arbitrate their own event"
+                );
+                self.event_arbitrators.insert(&event_id, &arbitrator_id);
+            }
+            None => {
+                self.event_arbitrators.remove(&event_id);
+            }
+        }
+    }
+
+    /// Opens a vote to cancel an active event. Only its sponsors can open it, and a new vote
+    /// can't be opened before the previous one is over.
+    pub fn open_cancellation_vote(&mut self, event_id: EventId) -> CancellationVote {
+        let account_id = env::predecessor_account_id();
+        let event = self.internal_watch_detail_event(&event_id);
+        require!(event.status == Status::Active, "The event is not active");
+        require!(
+            self.internal_unwrap_balance(&account_id, &event_id).is_ok(),
+            "Only sponsors of the event can open a cancellation vote"
+        );
+        let round = match self.cancellation_votes.get(&event_id) {
+            Some(vote) => {
+                require!(
+                    env::block_timestamp() > vote.deadline.0,
+                    "A cancellation vote is already open"
+                );
+                vote.round + 1
+            }
+            None => 1,
+        };
+
+        let vote = CancellationVote {
+            round,
+            deadline: U64(env::block_timestamp() + CANCELLATION_VOTING_PERIOD),
+            quorum_bps: CANCELLATION_QUORUM_BPS,
+            votes_near: U128(0),
+            votes_usdt: U128(0),
+            voters: 0,
+            passed: false,
+        };
+        self.cancellation_votes.insert(&event_id, &vote);
+
+        CancellationVoteOpen {
+            event_id: &event_id,
+            account_id: &account_id,
+            round,
+            deadline: &vote.deadline,
+        }
+        .emit();
+        vote
+    }
+
+    /// Votes for the cancellation of the event with the weight of the caller's sponsorship.
+    /// The event is cancelled as soon as the votes reach the quorum in every token it holds,
+    /// and sponsors can then `claim` their funds back.
+    /// The attached deposit covers the storage of the vote, the rest is refunded.
+    #[payable]
+    pub fn vote_cancellation(&mut self, event_id: EventId) -> CancellationVote {
+        let init_storage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+        let mut event = self.internal_watch_detail_event(&event_id);
+        require!(event.status == Status::Active, "The event is not active");
+        let mut vote = self
+            .cancellation_votes
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic_str("No cancellation vote is open"));
+        require!(
+            env::block_timestamp() <= vote.deadline.0,
+            "The cancellation vote is over"
+        );
+        let weight = self
+            .internal_unwrap_balance(&account_id, &event_id)
+            .unwrap_or_else(|_| env::panic_str("Only sponsors of the event can vote"));
+        require!(
+            self.cancellation_voters
+                .insert(&(event_id.clone(), vote.round, account_id.clone())),
+            "You have already voted in this cancellation vote"
+        );
+
+        vote.votes_near = U128(vote.votes_near.0 + weight.token_near);
+        vote.votes_usdt = U128(vote.votes_usdt.0 + weight.token_usdt);
+        vote.voters += 1;
+        let quorum = vote.quorum_bps as u128;
+        let totals = self.internal_voting_totals(&event_id);
+        vote.passed = [
+            (vote.votes_near.0, totals.token_near),
+            (vote.votes_usdt.0, totals.token_usdt),
+        ]
+        .iter()
+        .all(|(votes, total)| votes * BPS_DENOMINATOR >= total * quorum);
+        self.cancellation_votes.insert(&event_id, &vote);
+
+        CancellationVoteCast {
+            event_id: &event_id,
+            account_id: &account_id,
+            weight_near: &U128(weight.token_near),
+            weight_usdt: &U128(weight.token_usdt),
+            passed: vote.passed,
+        }
+        .emit();
+
+        if vote.passed {
+            self.internal_cancel_event(&mut event);
+            self.events.insert(&event_id, &event);
+        }
+
+        refund_deposit(init_storage);
+        vote
+    }
+
+    /// Settles a dispute over an active or finished event. Only its arbitrator can rule, once.
+    /// The owner's share of the funds not yet released through milestones is transferred right
+    /// away, and kept for `withdraw_unpaid_ruling_funds` if the transfer fails; when sponsors get
+    /// something back the event is cancelled so that they can `claim` it.
+    pub fn rule_dispute(&mut self, event_id: EventId, ruling: Ruling) {
+        let arbitrator_id = env::predecessor_account_id();
+        let mut event = self.internal_watch_detail_event(&event_id);
+        require!(
+            self.event_arbitrators.get(&event_id) == Some(arbitrator_id.clone()),
+            "Only the arbitrator of the event can rule on it"
+        );
+        require!(
+            event.status == Status::Active || event.status == Status::Finish,
+            "The event is already cancelled"
+        );
+        require!(
+            !self.dispute_rulings.contains_key(&event_id),
+            "The dispute has already been settled"
+        );
+        require!(
+            !self.prize_distributions.contains_key(&event_id),
+            "The prizes of the event have already been distributed"
+        );
+        require!(
+            ruling.owner_bps() as u128 <= BPS_DENOMINATOR,
+            "The owner share should be at most 10000 basis points"
+        );
+
+        // Every sponsor is refunded the same share, so the owner is paid the rest of the totals
+        let owner_amount = |total: Balance| {
+            let remaining = self.internal_refundable_amount(&event_id, total);
+            remaining * ruling.owner_bps() as u128 / BPS_DENOMINATOR
+        };
+        let (near, usdt) = (
+            owner_amount(event.total_near),
+            owner_amount(event.total_usdt),
+        );
+        // From now on `claim` only returns the sponsors' share
+        self.dispute_rulings.insert(&event_id, &ruling);
+        if let Some(mut escrow) = self.milestone_escrows.get(&event_id) {
+            escrow.vote = None;
+            self.milestone_escrows.insert(&event_id, &escrow);
+        }
+
+        self.internal_pay_ruling(&event_id, near, usdt);
+        if ruling == Ruling::Release {
+            self.internal_set_event_status(&mut event, Status::Finish);
+        } else {
+            self.internal_cancel_event(&mut event);
+        }
+        self.events.insert(&event_id, &event);
+
+        DisputeRuling {
+            event_id: &event_id,
+            arbitrator_id: &arbitrator_id,
+            ruling: &ruling,
+            owner_near: &U128(near),
+            owner_usdt: &U128(usdt),
+        }
+        .emit();
+    }
+
+    /// Sends the owner their share of a ruling again after the transfer failed. The finance
+    /// managers of the event can also trigger it.
+    pub fn withdraw_unpaid_ruling_funds(&mut self, event_id: EventId) {
+        require!(
+            self.check_event_role(&event_id, EventRole::Finance),
+            "Only the owner of the event can withdraw its funds"
+        );
+        let unpaid = self
+            .unpaid_rulings
+            .remove(&event_id)
+            .unwrap_or_else(|| env::panic_str("No ruling funds to withdraw"));
+        self.internal_pay_ruling(&event_id, unpaid.token_near, unpaid.token_usdt);
+    }
+
+    pub fn get_unpaid_ruling_funds(&self, event_id: EventId) -> Option<Amount> {
+        self.unpaid_rulings.get(&event_id)
+    }
+
+    pub fn get_event_arbitrator(&self, event_id: EventId) -> Option<AccountId> {
+        self.event_arbitrators.get(&event_id)
+    }
+
+    pub fn get_cancellation_vote(&self, event_id: EventId) -> Option<CancellationVote> {
+        self.cancellation_votes.get(&event_id)
+    }
+
+    pub fn get_dispute_ruling(&self, event_id: EventId) -> Option<Ruling> {
+        self.dispute_rulings.get(&event_id)
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_pay_ruling(&mut self, event_id: &EventId, near: Balance, usdt: Balance) {
+        let owner_id = self.internal_watch_detail_event(event_id).owner;
+        for (token, amount) in [(Token::NEAR, near), (Token::USDT, usdt)] {
+            if amount == 0 {
+                continue;
+            }
+            if let Some(promise) = self.internal_send_token(
+                &token,
+                &owner_id,
+                amount,
+                Some(format!("Ruling on event {}", event_id)),
+            ) {
+                promise.then(
+                    ext_self::ext(env::current_account_id())
+                        .with_static_gas(FT_TRANSFER_GAS)
+                        .ruling_payout_callback(event_id.clone(), token, amount.into()),
+                );
+            }
+        }
+    }
+}
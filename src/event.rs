@@ -17,11 +17,13 @@ pub enum Status {
     Cancel,
 }
 
-#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Eq, Debug, Clone)]
 #[serde(crate = "near_sdk::serde")]
+#[allow(clippy::upper_case_acronyms)]
 pub enum Token {
     USDT,
     NEAR,
+    PLAT,
 }
 #[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Eq, Debug)]
 #[serde(crate = "near_sdk::serde")]
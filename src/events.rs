@@ -12,19 +12,30 @@
 //! These events can be logged by calling `.emit()` on them if a single event, or calling
 //! [`FtMint::emit_many`], [`FtTransfer::emit_many`],
 //! or [`FtBurn::emit_many`] respectively.
+//!
+//! Sponsorship receipts are logged as nep171 (Non-Fungible Token) events: [`NftMint`],
+//! [`NftTransfer`] and [`NftBurn`].
+//!
+//! Events of the Plats features built on top of the token (tasks, campaigns, sponsorships)
+//! are logged in the same format under the `plats` standard.
 
-use near_sdk::json_types::U128;
+use near_sdk::json_types::{Base58CryptoHash, U128, U64};
 use near_sdk::AccountId;
 use near_sdk::serde::Serialize;
 
 use near_sdk::env;
 
+use crate::dispute::Ruling;
+use crate::event::Token;
+
 #[derive(Serialize, Debug)]
 #[serde(tag = "standard")]
 #[must_use = "don't forget to `.emit()` this event"]
 #[serde(rename_all = "snake_case")]
 pub(crate) enum NearEvent<'a> {
     Nep141(Nep141Event<'a>),
+    Nep171(Nep171Event<'a>),
+    Plats(PlatsEvent<'a>),
 }
 
 impl<'a> NearEvent<'a> {
@@ -118,4 +129,442 @@ fn new_141<'a>(version: &'static str, event_kind: Nep141EventKind<'a>) -> NearEv
 
 fn new_141_v1(event_kind: Nep141EventKind) -> NearEvent {
     new_141("1.0.0", event_kind)
-}
\ No newline at end of file
+}
+
+/// Data to log for an NFT mint event. To log this event, call [`.emit()`](NftMint::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct NftMint<'a> {
+    pub owner_id: &'a AccountId,
+    pub token_ids: &'a [&'a str],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+impl NftMint<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an NFT mint event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`NftMint`] represents the data of each mint.
+    pub fn emit_many(data: &[NftMint<'_>]) {
+        new_171_v1(Nep171EventKind::NftMint(data)).emit()
+    }
+}
+
+/// Data to log for an NFT transfer event. To log this event,
+/// call [`.emit()`](NftTransfer::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct NftTransfer<'a> {
+    pub old_owner_id: &'a AccountId,
+    pub new_owner_id: &'a AccountId,
+    pub token_ids: &'a [&'a str],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+impl NftTransfer<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an NFT transfer event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`NftTransfer`] represents the data of each transfer.
+    pub fn emit_many(data: &[NftTransfer<'_>]) {
+        new_171_v1(Nep171EventKind::NftTransfer(data)).emit()
+    }
+}
+
+/// Data to log for an NFT burn event. To log this event, call [`.emit()`](NftBurn::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct NftBurn<'a> {
+    pub owner_id: &'a AccountId,
+    pub token_ids: &'a [&'a str],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memo: Option<&'a str>,
+}
+
+impl NftBurn<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an NFT burn event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`NftBurn`] represents the data of each burn.
+    pub fn emit_many(data: &[NftBurn<'_>]) {
+        new_171_v1(Nep171EventKind::NftBurn(data)).emit()
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct Nep171Event<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    event_kind: Nep171EventKind<'a>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+#[allow(clippy::enum_variant_names)]
+enum Nep171EventKind<'a> {
+    NftMint(&'a [NftMint<'a>]),
+    NftTransfer(&'a [NftTransfer<'a>]),
+    NftBurn(&'a [NftBurn<'a>]),
+}
+
+fn new_171<'a>(version: &'static str, event_kind: Nep171EventKind<'a>) -> NearEvent<'a> {
+    NearEvent::Nep171(Nep171Event { version, event_kind })
+}
+
+fn new_171_v1(event_kind: Nep171EventKind) -> NearEvent {
+    new_171("1.0.0", event_kind)
+}
+
+/// Data to log when a task is closed and its unused budget refunded to the client.
+/// To log this event, call [`.emit()`](TaskClosed::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct TaskClosed<'a> {
+    pub task_id: &'a str,
+    pub client_id: &'a AccountId,
+    pub refund: &'a U128,
+    pub token: &'a Token,
+}
+
+impl TaskClosed<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a task closed event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`TaskClosed`] represents the data of each closed task.
+    pub fn emit_many(data: &[TaskClosed<'_>]) {
+        new_plats_v1(PlatsEventKind::TaskClosed(data)).emit()
+    }
+}
+
+/// Data to log when points are awarded to an account. To log this event,
+/// call [`.emit()`](PointsAward::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct PointsAward<'a> {
+    pub account_id: &'a AccountId,
+    pub amount: &'a U128,
+    pub season: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<&'a str>,
+}
+
+impl PointsAward<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a points award event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`PointsAward`] represents the data of each award.
+    pub fn emit_many(data: &[PointsAward<'_>]) {
+        new_plats_v1(PlatsEventKind::PointsAward(data)).emit()
+    }
+}
+
+/// Data to log when points are redeemed into PLAT. To log this event,
+/// call [`.emit()`](PointsRedeem::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct PointsRedeem<'a> {
+    pub account_id: &'a AccountId,
+    pub points: &'a U128,
+    pub amount: &'a U128,
+    pub season: u32,
+}
+
+impl PointsRedeem<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a points redeem event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`PointsRedeem`] represents the data of each redemption.
+    pub fn emit_many(data: &[PointsRedeem<'_>]) {
+        new_plats_v1(PlatsEventKind::PointsRedeem(data)).emit()
+    }
+}
+
+/// Data to log when a new points season starts. To log this event,
+/// call [`.emit()`](PointsSeasonStart::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct PointsSeasonStart {
+    pub season: u32,
+}
+
+impl PointsSeasonStart {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        new_plats_v1(PlatsEventKind::PointsSeasonStart(&[self])).emit()
+    }
+}
+
+/// Data to log when the winners of a raffle are drawn, with the seed they were drawn from.
+/// To log this event, call [`.emit()`](RaffleDraw::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RaffleDraw<'a> {
+    pub raffle_id: u64,
+    pub seed: &'a Base58CryptoHash,
+    pub winners: &'a [AccountId],
+    pub prize: &'a U128,
+    pub token: &'a Token,
+}
+
+impl RaffleDraw<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a raffle draw event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RaffleDraw`] represents the data of each draw.
+    pub fn emit_many(data: &[RaffleDraw<'_>]) {
+        new_plats_v1(PlatsEventKind::RaffleDraw(data)).emit()
+    }
+}
+
+/// Data to log when a sponsor opens a vote to cancel an event. To log this event,
+/// call [`.emit()`](CancellationVoteOpen::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct CancellationVoteOpen<'a> {
+    pub event_id: &'a str,
+    pub account_id: &'a AccountId,
+    pub round: u32,
+    pub deadline: &'a U64,
+}
+
+impl CancellationVoteOpen<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a cancellation vote opening event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`CancellationVoteOpen`] represents the data of each opening.
+    pub fn emit_many(data: &[CancellationVoteOpen<'_>]) {
+        new_plats_v1(PlatsEventKind::CancellationVoteOpen(data)).emit()
+    }
+}
+
+/// Data to log when a sponsor votes to cancel an event. To log this event,
+/// call [`.emit()`](CancellationVoteCast::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct CancellationVoteCast<'a> {
+    pub event_id: &'a str,
+    pub account_id: &'a AccountId,
+    pub weight_near: &'a U128,
+    pub weight_usdt: &'a U128,
+    /// Whether this vote made the cancellation pass
+    pub passed: bool,
+}
+
+impl CancellationVoteCast<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a cancellation vote event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`CancellationVoteCast`] represents the data of each vote.
+    pub fn emit_many(data: &[CancellationVoteCast<'_>]) {
+        new_plats_v1(PlatsEventKind::CancellationVoteCast(data)).emit()
+    }
+}
+
+/// Data to log when the arbitrator of an event rules on its funds. To log this event,
+/// call [`.emit()`](DisputeRuling::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct DisputeRuling<'a> {
+    pub event_id: &'a str,
+    pub arbitrator_id: &'a AccountId,
+    pub ruling: &'a Ruling,
+    /// Amounts sent to the owner of the event
+    pub owner_near: &'a U128,
+    pub owner_usdt: &'a U128,
+}
+
+impl DisputeRuling<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a dispute ruling event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`DisputeRuling`] represents the data of each ruling.
+    pub fn emit_many(data: &[DisputeRuling<'_>]) {
+        new_plats_v1(PlatsEventKind::DisputeRuling(data)).emit()
+    }
+}
+
+/// Data to log for each sponsorship whose unclaimed refund is swept to the treasury.
+/// To log this event, call [`.emit()`](RefundSwept::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct RefundSwept<'a> {
+    pub event_id: &'a str,
+    pub sponser_id: &'a AccountId,
+    pub treasury_id: &'a AccountId,
+    pub near: &'a U128,
+    pub usdt: &'a U128,
+}
+
+impl RefundSwept<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a refund sweep event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`RefundSwept`] represents the data of each swept sponsorship.
+    pub fn emit_many(data: &[RefundSwept<'_>]) {
+        new_plats_v1(PlatsEventKind::RefundSwept(data)).emit()
+    }
+}
+
+/// Data to log when a top-up or a withdrawal moves a sponsor to another tier. To log this event,
+/// call [`.emit()`](SponsorTierChange::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct SponsorTierChange<'a> {
+    pub event_id: &'a str,
+    pub sponser_id: &'a AccountId,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_tier: Option<&'a str>,
+    /// `None` when the sponsor fell below the lowest tier
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_tier: Option<&'a str>,
+}
+
+impl SponsorTierChange<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a tier change event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`SponsorTierChange`] represents the data of each change.
+    pub fn emit_many(data: &[SponsorTierChange<'_>]) {
+        new_plats_v1(PlatsEventKind::SponsorTierChange(data)).emit()
+    }
+}
+
+/// Data to log when a sponsor hands their sponsorship of an event to another account.
+/// To log this event, call [`.emit()`](SponsorshipTransfer::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct SponsorshipTransfer<'a> {
+    pub event_id: &'a str,
+    pub old_sponser_id: &'a AccountId,
+    pub new_sponser_id: &'a AccountId,
+    pub near: &'a U128,
+    pub usdt: &'a U128,
+    /// Whether the sponsorship was added to an existing sponsorship of the receiver
+    pub merged: bool,
+}
+
+impl SponsorshipTransfer<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits a sponsorship transfer event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`SponsorshipTransfer`] represents the data of each transfer.
+    pub fn emit_many(data: &[SponsorshipTransfer<'_>]) {
+        new_plats_v1(PlatsEventKind::SponsorshipTransfer(data)).emit()
+    }
+}
+
+/// Data to log when the ownership of an event is transferred. To log this event,
+/// call [`.emit()`](EventOwnerChange::emit).
+#[must_use]
+#[derive(Serialize, Debug, Clone)]
+pub struct EventOwnerChange<'a> {
+    pub event_id: &'a str,
+    pub old_owner_id: &'a AccountId,
+    pub new_owner_id: &'a AccountId,
+}
+
+impl EventOwnerChange<'_> {
+    /// Logs the event to the host. This is required to ensure that the event is triggered
+    /// and to consume the event.
+    pub fn emit(self) {
+        Self::emit_many(&[self])
+    }
+
+    /// Emits an ownership change event, through [`env::log_str`](near_sdk::env::log_str),
+    /// where each [`EventOwnerChange`] represents the data of each change.
+    pub fn emit_many(data: &[EventOwnerChange<'_>]) {
+        new_plats_v1(PlatsEventKind::EventOwnerChange(data)).emit()
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub(crate) struct PlatsEvent<'a> {
+    version: &'static str,
+    #[serde(flatten)]
+    event_kind: PlatsEventKind<'a>,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(tag = "event", content = "data")]
+#[serde(rename_all = "snake_case")]
+enum PlatsEventKind<'a> {
+    TaskClosed(&'a [TaskClosed<'a>]),
+    PointsAward(&'a [PointsAward<'a>]),
+    PointsRedeem(&'a [PointsRedeem<'a>]),
+    PointsSeasonStart(&'a [PointsSeasonStart]),
+    RaffleDraw(&'a [RaffleDraw<'a>]),
+    CancellationVoteOpen(&'a [CancellationVoteOpen<'a>]),
+    CancellationVoteCast(&'a [CancellationVoteCast<'a>]),
+    DisputeRuling(&'a [DisputeRuling<'a>]),
+    RefundSwept(&'a [RefundSwept<'a>]),
+    SponsorTierChange(&'a [SponsorTierChange<'a>]),
+    SponsorshipTransfer(&'a [SponsorshipTransfer<'a>]),
+    EventOwnerChange(&'a [EventOwnerChange<'a>]),
+}
+
+fn new_plats<'a>(version: &'static str, event_kind: PlatsEventKind<'a>) -> NearEvent<'a> {
+    NearEvent::Plats(PlatsEvent {
+        version,
+        event_kind,
+    })
+}
+
+fn new_plats_v1(event_kind: PlatsEventKind) -> NearEvent {
+    new_plats("1.0.0", event_kind)
+}
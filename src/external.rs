@@ -18,6 +18,25 @@ pub trait CallbackSelf {
     );
     fn balance_of_callback(&self, account_id: &AccountId) -> Balance;
     fn storage_deposit_callback_add_token(&mut self);
+    fn task_payout_callback(&mut self, task_id: TaskId, receiver_id: AccountId, amount: U128);
+    fn task_refund_callback(&mut self, task_id: TaskId, amount: U128);
+    fn raffle_payout_callback(&mut self, raffle_id: RaffleId, receiver_id: AccountId, amount: U128);
+    fn prize_payout_callback(&mut self, event_id: EventId, index: u64);
+    fn milestone_release_callback(&mut self, event_id: EventId, token: Token, amount: U128);
+    fn ruling_payout_callback(&mut self, event_id: EventId, token: Token, amount: U128);
+    fn withdraw_fees_callback(&mut self, token: Token, amount: U128);
+    fn sweep_unclaimed_callback(&mut self, token: Token, amount: U128);
+    fn fee_refund_callback(&mut self, event_id: EventId, token: Token, amount: U128);
+    fn withdraw_sponsorship_callback(
+        &mut self,
+        event_id: EventId,
+        sponser_id: AccountId,
+        token: Token,
+        amount: U128,
+        penalty: U128,
+        sponsored_at: U64,
+    );
+    fn price_data_callback(&mut self);
 }
 #[ext_contract(ext_ft_fungible_token)]
 pub trait FungibleTokenCore {
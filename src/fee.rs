@@ -0,0 +1,308 @@
+use crate::external::ext_self;
+use crate::prize::BPS_DENOMINATOR;
+use crate::*;
+
+/// Fees taken on the sponsorships of an event.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventFees {
+    /// Fees earned, net of the fees refunded to sponsors
+    pub near: U128,
+    pub usdt: U128,
+    /// Part of the fees already moved to the treasury balances
+    pub settled_near: U128,
+    pub settled_usdt: U128,
+}
+
+impl Default for EventFees {
+    fn default() -> Self {
+        Self {
+            near: U128(0),
+            usdt: U128(0),
+            settled_near: U128(0),
+            settled_usdt: U128(0),
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the fee taken on every sponsorship, in basis points, for tokens without a fee of their own.
+    pub fn set_platform_fee(&mut self, fee_bps: u16) {
+        self.assert_owner();
+        require!(
+            fee_bps as u128 <= BPS_DENOMINATOR,
+            "The fee should be at most 10000 basis points"
+        );
+        self.platform_fee_bps = fee_bps;
+    }
+
+    /// Sets the fee of the sponsorships in `token`, or makes it use the platform fee again.
+    pub fn set_token_fee(&mut self, token: Token, fee_bps: Option<u16>) {
+        self.assert_owner();
+        match fee_bps {
+            Some(fee_bps) => {
+                require!(
+                    fee_bps as u128 <= BPS_DENOMINATOR,
+                    "The fee should be at most 10000 basis points"
+                );
+                self.token_fees.insert(&token, &fee_bps);
+            }
+            None => {
+                self.token_fees.remove(&token);
+            }
+        }
+    }
+
+    /// Sets whether sponsors get their fees back when they `claim` the refund of a cancelled event.
+    /// The policy applies to the events cancelled after the change.
+    pub fn set_refund_fees_on_cancel(&mut self, refund: bool) {
+        self.assert_owner();
+        self.refund_fees_on_cancel = refund;
+    }
+
+    /// Moves the fees of an event to the treasury balances once they can no longer be refunded:
+    /// when the event is finished, or cancelled without fee refunds. The treasurer and the
+    /// finance managers of the event can settle them.
+    pub fn settle_event_fees(&mut self, event_id: EventId) -> (U128, U128) {
+        let event = self.internal_watch_detail_event(&event_id);
+        require!(
+            env::predecessor_account_id() == self.treasury_id
+                || self.check_event_role(&event_id, EventRole::Finance),
+            "You are not allowed to settle the fees of this event"
+        );
+        require!(
+            event.status == Status::Finish
+                || (event.status == Status::Cancel && !self.fee_refund_events.contains(&event_id)),
+            "The fees of the event can still be refunded"
+        );
+        let mut fees = self.event_fees.get(&event_id).unwrap_or_default();
+        let near = fees.near.0 - fees.settled_near.0;
+        let usdt = fees.usdt.0 - fees.settled_usdt.0;
+        require!(near > 0 || usdt > 0, "No fees to settle");
+        fees.settled_near = fees.near;
+        fees.settled_usdt = fees.usdt;
+        self.event_fees.insert(&event_id, &fees);
+
+        self.internal_credit_treasury(&Token::NEAR, near);
+        self.internal_credit_treasury(&Token::USDT, usdt);
+        (near.into(), usdt.into())
+    }
+
+    /// Sends `amount` of the settled fees in `token` to the treasurer.
+    pub fn withdraw_fees(&mut self, token: Token, amount: U128) {
+        let treasury_id = env::predecessor_account_id();
+        require!(
+            treasury_id == self.treasury_id,
+            "Only the treasurer can withdraw the fees"
+        );
+        require!(amount.0 > 0, "The amount should be a positive number");
+        let balance = self.treasury_balances.get(&token).unwrap_or(0);
+        let new_balance = balance
+            .checked_sub(amount.0)
+            .unwrap_or_else(|| env::panic_str("The treasury doesn't have enough balance"));
+        self.treasury_balances.insert(&token, &new_balance);
+
+        self.internal_send_token(
+            &token,
+            &treasury_id,
+            amount.0,
+            Some("Platform fees".to_string()),
+        )
+        .unwrap_or_else(|| env::panic_str("Fees are taken in NEAR or USDT"))
+        .then(
+            ext_self::ext(env::current_account_id())
+                .with_static_gas(FT_TRANSFER_GAS)
+                .withdraw_fees_callback(token, amount),
+        );
+    }
+
+    /// Returns the fee of the sponsorships in `token`, or the platform fee.
+    pub fn get_platform_fee(&self, token: Option<Token>) -> u16 {
+        token
+            .and_then(|token| self.token_fees.get(&token))
+            .unwrap_or(self.platform_fee_bps)
+    }
+
+    pub fn get_refund_fees_on_cancel(&self) -> bool {
+        self.refund_fees_on_cancel
+    }
+
+    /// Whether the sponsors of the cancelled event get their fees back with their refund.
+    pub fn are_event_fees_refunded(&self, event_id: EventId) -> bool {
+        self.fee_refund_events.contains(&event_id)
+    }
+
+    pub fn get_event_fees(&self, event_id: EventId) -> EventFees {
+        self.event_fees.get(&event_id).unwrap_or_default()
+    }
+
+    /// Fees earned in `token` over every event, settled or not.
+    pub fn get_total_fees(&self, token: Token) -> U128 {
+        self.total_fees.get(&token).unwrap_or(0).into()
+    }
+
+    /// Settled fees and withdrawal penalties in `token` that the treasurer can withdraw.
+    pub fn get_treasury_balance(&self, token: Token) -> U128 {
+        self.treasury_balances.get(&token).unwrap_or(0).into()
+    }
+}
+
+impl Contract {
+    /// Takes the platform fee out of a sponsorship and returns what goes to the event.
+    pub(crate) fn internal_take_fee(
+        &mut self,
+        sponser_id: &AccountId,
+        event_id: &EventId,
+        token: &Token,
+        amount: Balance,
+    ) -> Balance {
+        let fee = amount * self.get_platform_fee(Some(token.clone())) as u128 / BPS_DENOMINATOR;
+        if fee == 0 {
+            return amount;
+        }
+
+        let key = (event_id.clone(), sponser_id.clone());
+        let mut sponser_fees = self.sponser_fees.get(&key).unwrap_or(Amount {
+            token_near: 0,
+            token_usdt: 0,
+        });
+        if *token == Token::NEAR {
+            sponser_fees.token_near += fee;
+        } else {
+            sponser_fees.token_usdt += fee;
+        }
+        self.sponser_fees.insert(&key, &sponser_fees);
+        self.internal_add_event_fees(event_id, token, fee);
+        amount - fee
+    }
+
+    /// Records whether the sponsors of an event being cancelled get their fees back. Fees that
+    /// were already settled, e.g. when the event was finished before a dispute, are never refunded.
+    pub(crate) fn internal_record_fee_refund_policy(&mut self, event_id: &EventId) {
+        let fees = self.event_fees.get(event_id).unwrap_or_default();
+        if self.refund_fees_on_cancel && fees.settled_near.0 == 0 && fees.settled_usdt.0 == 0 {
+            self.fee_refund_events.insert(event_id);
+        }
+    }
+
+    /// Sends a sponsor back the fees of their sponsorship when the policy of the event refunds them.
+    pub(crate) fn internal_refund_fees(&mut self, sponser_id: &AccountId, event_id: &EventId) {
+        if !self.fee_refund_events.contains(event_id) {
+            return;
+        }
+        let sponser_fees = match self
+            .sponser_fees
+            .remove(&(event_id.clone(), sponser_id.clone()))
+        {
+            Some(sponser_fees) => sponser_fees,
+            None => return,
+        };
+        for (token, amount) in [
+            (Token::NEAR, sponser_fees.token_near),
+            (Token::USDT, sponser_fees.token_usdt),
+        ] {
+            if amount == 0 {
+                continue;
+            }
+            self.internal_remove_event_fees(event_id, &token, amount);
+            self.internal_send_token(
+                &token,
+                sponser_id,
+                amount,
+                Some(format!("Fee refund of event {}", event_id)),
+            )
+            .unwrap()
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(FT_TRANSFER_GAS)
+                    .fee_refund_callback(event_id.clone(), token, amount.into()),
+            );
+        }
+    }
+
+    pub(crate) fn internal_add_event_fees(
+        &mut self,
+        event_id: &EventId,
+        token: &Token,
+        amount: Balance,
+    ) {
+        let mut fees = self.event_fees.get(event_id).unwrap_or_default();
+        if *token == Token::NEAR {
+            fees.near = U128(fees.near.0 + amount);
+        } else {
+            fees.usdt = U128(fees.usdt.0 + amount);
+        }
+        self.event_fees.insert(event_id, &fees);
+        let total = self.total_fees.get(token).unwrap_or(0);
+        self.total_fees.insert(token, &(total + amount));
+    }
+
+    pub(crate) fn internal_remove_event_fees(
+        &mut self,
+        event_id: &EventId,
+        token: &Token,
+        amount: Balance,
+    ) {
+        let mut fees = self.event_fees.get(event_id).unwrap_or_default();
+        if *token == Token::NEAR {
+            fees.near = U128(fees.near.0 - amount);
+        } else {
+            fees.usdt = U128(fees.usdt.0 - amount);
+        }
+        self.event_fees.insert(event_id, &fees);
+        let total = self.total_fees.get(token).unwrap_or(0);
+        self.total_fees.insert(token, &(total - amount));
+    }
+
+    /// Settles the fees of a sponsor whose refund was swept to the treasury: the fees were
+    /// refundable with it, and nobody can claim them anymore.
+    pub(crate) fn internal_settle_sponser_fees(
+        &mut self,
+        sponser_id: &AccountId,
+        event_id: &EventId,
+    ) {
+        let sponser_fees = match self
+            .sponser_fees
+            .remove(&(event_id.clone(), sponser_id.clone()))
+        {
+            Some(sponser_fees) => sponser_fees,
+            None => return,
+        };
+        if !self.fee_refund_events.contains(event_id) {
+            // the fees of the event are settled all at once
+            return;
+        }
+        let mut fees = self.event_fees.get(event_id).unwrap_or_default();
+        fees.settled_near = U128(fees.settled_near.0 + sponser_fees.token_near);
+        fees.settled_usdt = U128(fees.settled_usdt.0 + sponser_fees.token_usdt);
+        self.event_fees.insert(event_id, &fees);
+        self.internal_credit_treasury(&Token::NEAR, sponser_fees.token_near);
+        self.internal_credit_treasury(&Token::USDT, sponser_fees.token_usdt);
+    }
+
+    /// Keeps fees that couldn't be refunded to a sponsor as settled fees of the event.
+    pub(crate) fn internal_keep_refused_fees(
+        &mut self,
+        event_id: &EventId,
+        token: &Token,
+        amount: Balance,
+    ) {
+        self.internal_add_event_fees(event_id, token, amount);
+        let mut fees = self.event_fees.get(event_id).unwrap();
+        if *token == Token::NEAR {
+            fees.settled_near = U128(fees.settled_near.0 + amount);
+        } else {
+            fees.settled_usdt = U128(fees.settled_usdt.0 + amount);
+        }
+        self.event_fees.insert(event_id, &fees);
+        self.internal_credit_treasury(token, amount);
+    }
+
+    pub(crate) fn internal_credit_treasury(&mut self, token: &Token, amount: Balance) {
+        if amount > 0 {
+            let balance = self.treasury_balances.get(token).unwrap_or(0);
+            self.treasury_balances.insert(token, &(balance + amount));
+        }
+    }
+}
@@ -116,12 +116,126 @@ impl FungibleTokenCore for Contract {
     }
 }
 
+/// Outcome of a single recipient of `ft_batch_transfer`.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+#[serde(rename_all = "snake_case")]
+pub enum BatchTransferStatus {
+    Transferred,
+    /// The recipient is not registered and the caller asked to skip such recipients.
+    Skipped,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct BatchTransferResult {
+    pub receiver_id: AccountId,
+    pub amount: U128,
+    pub status: BatchTransferStatus,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Transfers tokens from the caller to several receivers at once. The sender's balance is
+    /// checked once for the sum of all credited amounts and a single `ft_transfer` event is logged.
+    ///
+    /// Arguments:
+    /// - `transfers` - the receivers and the amount each of them gets.
+    /// - `memo` - an optional memo shared by every transfer.
+    /// - `skip_unregistered` - if `true`, unregistered receivers are skipped instead of failing the whole batch.
+    ///
+    /// Returns the outcome for each receiver, in the order they were given.
+    #[payable]
+    pub fn ft_batch_transfer(
+        &mut self,
+        transfers: Vec<(AccountId, U128)>,
+        memo: Option<String>,
+        skip_unregistered: Option<bool>,
+    ) -> Vec<BatchTransferResult> {
+        // Assert that the user attached exactly 1 yoctoNEAR, like a single transfer.
+        assert_one_yocto();
+        require!(!transfers.is_empty(), "There should be at least one transfer");
+        let sender_id = env::predecessor_account_id();
+        let skip_unregistered = skip_unregistered.unwrap_or(false);
+
+        let mut results = Vec::with_capacity(transfers.len());
+        let mut total: Balance = 0;
+        for (receiver_id, amount) in transfers {
+            require!(
+                sender_id != receiver_id,
+                "Sender and receiver should be different"
+            );
+            require!(amount.0 > 0, "The amount should be a positive number");
+            let status = if self.accounts.contains_key(&receiver_id) {
+                total = total
+                    .checked_add(amount.0)
+                    .unwrap_or_else(|| env::panic_str("Balance overflow"));
+                BatchTransferStatus::Transferred
+            } else if skip_unregistered {
+                BatchTransferStatus::Skipped
+            } else {
+                env::panic_str(format!("The account {} is not registered", &receiver_id).as_str())
+            };
+            results.push(BatchTransferResult {
+                receiver_id,
+                amount,
+                status,
+            });
+        }
+
+        // Withdraw the whole batch from the sender once, then credit each receiver
+        if total > 0 {
+            self.internal_withdraw(&sender_id, total);
+        }
+        let transferred: Vec<&BatchTransferResult> = results
+            .iter()
+            .filter(|result| result.status == BatchTransferStatus::Transferred)
+            .collect();
+        for result in transferred.iter() {
+            self.internal_deposit(&result.receiver_id, result.amount.0);
+        }
+
+        // Emit a single Transfer event covering every credited receiver
+        if !transferred.is_empty() {
+            let events: Vec<FtTransfer> = transferred
+                .iter()
+                .map(|result| FtTransfer {
+                    old_owner_id: &sender_id,
+                    new_owner_id: &result.receiver_id,
+                    amount: &result.amount,
+                    memo: memo.as_deref(),
+                })
+                .collect();
+            FtTransfer::emit_many(&events);
+        }
+
+        results
+    }
+}
+
+/// Message of an `ft_transfer_call` of PLAT or USDT to this contract that funds a task.
 #[derive(Serialize, Deserialize)]
 #[serde(crate = "near_sdk::serde")]
 struct PaymentInfo {
     task_id: String,
 }
 
+/// Message of an `ft_transfer_call` of USDT to this contract that sponsors an event, or tops up
+/// the sponsorship of the sender, with a memo kept in the contribution ledger.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+struct SponsorshipInfo {
+    event_id: EventId,
+    memo: Option<String>,
+}
+
+/// Message of an `ft_transfer_call` of USDT to this contract that funds the prize pool of a raffle.
+#[derive(Serialize, Deserialize)]
+#[serde(crate = "near_sdk::serde")]
+struct RaffleFunding {
+    raffle_id: RaffleId,
+}
+
 #[ext_contract(ext_ft_receiver)]
 pub trait FungibleTokenReceiver {
     /// Called by fungible token contract after `ft_transfer_call` was initiated by
@@ -153,21 +267,85 @@ pub trait FungibleTokenReceiver {
 #[near_bindgen]
 impl FungibleTokenReceiver for Contract {
     /// Callback on receiving tokens by this contract.
-    #[allow(unreachable_code)]
     fn ft_on_transfer(
         &mut self,
         sender_id: AccountId,
         amount: U128,
         msg: String,
     ) -> PromiseOrValue<U128> {
+        // PLAT sent to this contract itself funds the task given in the message
+        if env::predecessor_account_id() == env::current_account_id() {
+            let json_result: PaymentInfo =
+                near_sdk::serde_json::from_str(&msg).expect("Not valid payment args");
+            self.internal_fund_task(&sender_id, &json_result.task_id, Token::PLAT, amount.into());
+            return PromiseOrValue::Value(U128(0));
+        }
+        // USDT sent with a payment message funds a task paid in USDT
+        if let Ok(json_result) = near_sdk::serde_json::from_str::<PaymentInfo>(&msg) {
+            require!(
+                env::predecessor_account_id().as_str() == USDT_TOKEN_ID,
+                "Tasks can only be funded in PLAT or USDT"
+            );
+            self.internal_fund_task(&sender_id, &json_result.task_id, Token::USDT, amount.into());
+            return PromiseOrValue::Value(U128(0));
+        }
+        // USDT sent with a raffle message funds the prize pool of the raffle
+        if let Ok(json_result) = near_sdk::serde_json::from_str::<RaffleFunding>(&msg) {
+            require!(
+                env::predecessor_account_id().as_str() == USDT_TOKEN_ID,
+                "The prize pool can only be funded in USDT"
+            );
+            self.internal_fund_raffle(
+                &sender_id,
+                json_result.raffle_id,
+                Token::USDT,
+                amount.into(),
+            );
+            return PromiseOrValue::Value(U128(0));
+        }
+        // USDT sent with a sponsorship message sponsors the event
+        if let Ok(json_result) = near_sdk::serde_json::from_str::<SponsorshipInfo>(&msg) {
+            require!(
+                env::predecessor_account_id().as_str() == USDT_TOKEN_ID,
+                "Sponsorships with a memo can only be paid in USDT"
+            );
+            assert_min_usdt_contribution(amount.0);
+            let event_id = json_result.event_id;
+            self.internal_watch_detail_event(&event_id);
+            let accepted =
+                self.internal_limit_sponsorship(&sender_id, &event_id, &Token::USDT, amount.into());
+            if self.internal_unwrap_balance(&sender_id, &event_id).is_ok() {
+                self.internal_more_sponse_usdt(&sender_id, &event_id, accepted, json_result.memo);
+            } else {
+                self.internal_sponse(
+                    &sender_id,
+                    &event_id,
+                    accepted,
+                    Token::USDT,
+                    json_result.memo,
+                );
+            }
+            // the part over the limits of the event is returned to the sponsor
+            return PromiseOrValue::Value(U128(amount.0 - accepted));
+        }
+
         if msg != "" {
+            assert_min_usdt_contribution(amount.0);
             let split_msg: Vec<&str> = msg.split(" ").collect();
             if split_msg.len() == 1 {
                 if self.check_exist_event(&msg) {
                     let sender_id = env::signer_account_id();
-                    let result = self.internal_sponse(&sender_id, &msg, amount.into(), Token::USDT);
+                    let accepted = self.internal_limit_sponsorship(
+                        &sender_id,
+                        &msg,
+                        &Token::USDT,
+                        amount.into(),
+                    );
+                    let result =
+                        self.internal_sponse(&sender_id, &msg, accepted, Token::USDT, None);
+                    // the part over the limits of the event is returned to the sponsor
                     if result {
-                        PromiseOrValue::Value(U128(0))
+                        PromiseOrValue::Value(U128(amount.0 - accepted))
                     } else {
                         PromiseOrValue::Value(amount)
                     }
@@ -180,13 +358,16 @@ impl FungibleTokenReceiver for Contract {
                         "The message that the user deposited is not in the correct format",
                     )
                 });
-                let result = self.internal_more_sponse_usdt(
+                let event_id = String::from(*event_id);
+                let accepted = self.internal_limit_sponsorship(
                     &sender_id,
-                    &String::from(*event_id),
+                    &event_id,
+                    &Token::USDT,
                     amount.into(),
                 );
+                let result = self.internal_more_sponse_usdt(&sender_id, &event_id, accepted, None);
                 if result {
-                    PromiseOrValue::Value(U128(0))
+                    PromiseOrValue::Value(U128(amount.0 - accepted))
                 } else {
                     PromiseOrValue::Value(amount)
                 }
@@ -194,22 +375,6 @@ impl FungibleTokenReceiver for Contract {
         } else {
             PromiseOrValue::Value(amount)
         }
-
-        // let json_result: PaymentInfo =
-        //     near_sdk::serde_json::from_str(&msg).expect("Not valid payment args");
-
-        // let id = json_result.task_id;
-
-        // self.tasks.remove(&id);
-
-        // let task_info = TaskInfo {
-        //     client: sender_id,
-        //     amount: amount,
-        // };
-
-        // self.tasks.insert(&id, &task_info);
-
-        // PromiseOrValue::Value(U128(0))
     }
 }
 
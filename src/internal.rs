@@ -75,6 +75,60 @@ impl Contract {
         .emit();
     }
 
+    /// Internal method for registering the contract's own account so it can hold a balance in escrow
+    /// like any other account. Does nothing if it is already registered.
+    pub(crate) fn internal_register_contract_account(&mut self) {
+        let contract_id = env::current_account_id();
+        if !self.accounts.contains_key(&contract_id) {
+            self.internal_register_account(&contract_id);
+        }
+    }
+
+    /// Internal method for moving FTs from an account into the balance held in escrow by the contract.
+    pub(crate) fn internal_lock(
+        &mut self,
+        account_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) {
+        self.internal_register_contract_account();
+        self.internal_transfer(account_id, &env::current_account_id(), amount, memo);
+    }
+
+    /// Internal method for releasing FTs held in escrow by the contract to an account.
+    pub(crate) fn internal_unlock(
+        &mut self,
+        account_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) {
+        self.internal_transfer(&env::current_account_id(), account_id, amount, memo);
+    }
+
+    /// Internal method for paying out tokens held by the contract: NEAR and USDT are sent with a promise,
+    /// PLAT is released from escrow. Returns the transfer promise so callers can attach a callback.
+    pub(crate) fn internal_send_token(
+        &mut self,
+        token: &Token,
+        receiver_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) -> Option<Promise> {
+        match token {
+            Token::NEAR => Some(Promise::new(receiver_id.clone()).transfer(amount)),
+            Token::USDT => Some(
+                ext_ft_fungible_token::ext(USDT_TOKEN_ID.parse().unwrap())
+                    .with_attached_deposit(1)
+                    .with_static_gas(FT_TRANSFER_GAS)
+                    .ft_transfer(receiver_id.clone(), amount.into(), memo),
+            ),
+            Token::PLAT => {
+                self.internal_unlock(receiver_id, amount, memo);
+                None
+            }
+        }
+    }
+
     /// Internal method for registering an account with the contract.
     pub(crate) fn internal_register_account(&mut self, account_id: &AccountId) {
         if self.accounts.insert(account_id, &0).is_some() {
@@ -118,7 +172,10 @@ impl Contract {
         event_id: &EventId,
         amount: Balance,
         token: Token,
+        memo: Option<String>,
     ) -> bool {
+        self.assert_sponsorship_open(event_id);
+        let amount = self.internal_take_fee(account_id, event_id, &token, amount);
         match self.internal_unwrap_balance(account_id, event_id) {
             Ok(_) => env::panic_str("You have deposited this event before"),
             Err(_) => {
@@ -156,12 +213,38 @@ impl Contract {
 
                 let mut event = self.events.get(&event_id).unwrap();
                 event.sponsers.push(account_id.clone());
+                self.sponsored_at
+                    .insert(&(event_id.clone(), account_id.clone()), &env::block_timestamp());
+                self.internal_mint_receipt(account_id, event_id);
+                self.internal_update_leaderboards(
+                    account_id,
+                    event_id,
+                    &Amount {
+                        token_near: 0,
+                        token_usdt: 0,
+                    },
+                );
                 if token == Token::NEAR {
                     event.total_near += amount;
                 } else {
                     event.total_usdt += amount;
                 }
                 self.events.insert(&event_id, &event);
+                let new_total = if token == Token::NEAR {
+                    event.total_near
+                } else {
+                    event.total_usdt
+                };
+                self.internal_on_total_change(&event, &token, new_total - amount);
+                self.internal_track_contribution(account_id, event_id);
+                self.internal_record_contribution(
+                    account_id,
+                    event_id,
+                    &token,
+                    amount,
+                    ContributionKind::Initial,
+                    memo,
+                );
                 true
             }
         }
@@ -172,7 +255,11 @@ impl Contract {
         account_id: &AccountId,
         event_id: &EventId,
         balance: Balance,
+        memo: Option<String>,
     ) -> bool {
+        self.assert_sponsorship_open(event_id);
+        let balance = self.internal_take_fee(account_id, event_id, &Token::NEAR, balance);
+        let old_tier = self.internal_tier_index(account_id, event_id);
         match self.internal_unwrap_balance(account_id, event_id) {
             Ok(amount) => {
                 if let Some(new_balance) = amount.token_near.checked_add(balance) {
@@ -187,9 +274,25 @@ impl Contract {
                             };
                             sponse.map_event_amount.insert(event_id.clone(), new_amount);
                             self.sponser_to_sponse.insert(&account_id, &sponse);
+                            self.internal_update_leaderboards(account_id, event_id, &amount);
                             let mut event = self.events.get(&event_id).unwrap();
                             event.total_near += balance;
                             self.events.insert(event_id, &event);
+                            self.internal_on_total_change(
+                                &event,
+                                &Token::NEAR,
+                                event.total_near - balance,
+                            );
+                            self.internal_track_contribution(account_id, event_id);
+                            self.internal_record_contribution(
+                                account_id,
+                                event_id,
+                                &Token::NEAR,
+                                balance,
+                                ContributionKind::TopUp,
+                                memo,
+                            );
+                            self.internal_check_tier_change(account_id, event_id, old_tier);
                             true
                         }
                         None => env::panic_str("You hasn't deposit this event yet"),
@@ -207,7 +310,11 @@ impl Contract {
         account_id: &AccountId,
         event_id: &EventId,
         balance: Balance,
+        memo: Option<String>,
     ) -> bool {
+        self.assert_sponsorship_open(event_id);
+        let balance = self.internal_take_fee(account_id, event_id, &Token::USDT, balance);
+        let old_tier = self.internal_tier_index(account_id, event_id);
         match self.internal_unwrap_balance(account_id, event_id) {
             Ok(amount) => {
                 if let Some(new_balance) = amount.token_usdt.checked_add(balance) {
@@ -222,9 +329,25 @@ impl Contract {
                             };
                             sponse.map_event_amount.insert(event_id.clone(), new_amount);
                             self.sponser_to_sponse.insert(&account_id, &sponse);
+                            self.internal_update_leaderboards(account_id, event_id, &amount);
                             let mut event = self.events.get(&event_id).unwrap();
                             event.total_usdt += balance;
                             self.events.insert(event_id, &event);
+                            self.internal_on_total_change(
+                                &event,
+                                &Token::USDT,
+                                event.total_usdt - balance,
+                            );
+                            self.internal_track_contribution(account_id, event_id);
+                            self.internal_record_contribution(
+                                account_id,
+                                event_id,
+                                &Token::USDT,
+                                balance,
+                                ContributionKind::TopUp,
+                                memo,
+                            );
+                            self.internal_check_tier_change(account_id, event_id, old_tier);
                             true
                         }
                         None => env::panic_str("You hasn't deposit this event yet"),
@@ -257,7 +380,7 @@ impl Contract {
         amount: Balance,
         event_id: EventId,
     ) {
-        let token_id: AccountId = "ft1.tranchinh2001.testnet".parse().unwrap();
+        let token_id: AccountId = USDT_TOKEN_ID.parse().unwrap();
         ext_ft_fungible_token::ext(token_id.clone())
             .with_attached_deposit(1)
             .with_static_gas(FT_TRANSFER_GAS)
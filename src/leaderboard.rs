@@ -0,0 +1,213 @@
+use crate::*;
+
+/// Value the sponsors of an event are ranked by.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum Ranking {
+    NEAR,
+    USDT,
+    /// Both tokens converted in the reference token of the event, with the rate of its tiers
+    Normalized,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct LeaderboardEntry {
+    pub sponser_id: AccountId,
+    /// Value the sponsor is ranked by
+    pub value: U128,
+    pub near: U128,
+    pub usdt: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Returns the sponsors of the event from the largest sponsorship in `ranking`.
+    pub fn get_event_leaderboard(
+        &self,
+        event_id: EventId,
+        ranking: Ranking,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<LeaderboardEntry> {
+        self.internal_watch_detail_event(&event_id);
+        if ranking == Ranking::Normalized {
+            require!(
+                self.event_tiers.get(&event_id).is_some(),
+                "The event has no conversion rate, set its tiers first"
+            );
+        }
+        let leaderboard = match self.event_leaderboards.get(&(event_id.clone(), ranking)) {
+            Some(leaderboard) => leaderboard,
+            None => return vec![],
+        };
+        leaderboard
+            .iter_rev()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(50) as usize)
+            // a sponsor that is no longer in the event has no entry to show
+            .filter_map(|((value, sponser_id), _)| {
+                let amount = self.internal_unwrap_balance(&sponser_id, &event_id).ok()?;
+                Some(LeaderboardEntry {
+                    sponser_id,
+                    value: value.into(),
+                    near: amount.token_near.into(),
+                    usdt: amount.token_usdt.into(),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the sponsors from the largest total sponsorship in `token` over every event.
+    /// Events have their own conversion rates, so the global ranking is per token.
+    pub fn get_global_leaderboard(
+        &self,
+        token: Token,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<LeaderboardEntry> {
+        let leaderboard = match token {
+            Token::NEAR => &self.global_leaderboard_near,
+            Token::USDT => &self.global_leaderboard_usdt,
+            Token::PLAT => env::panic_str("Events are sponsored in NEAR or USDT"),
+        };
+        leaderboard
+            .iter_rev()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|((value, sponser_id), _)| {
+                let totals = self.sponsor_totals.get(&sponser_id).unwrap();
+                LeaderboardEntry {
+                    sponser_id,
+                    value: value.into(),
+                    near: totals.token_near.into(),
+                    usdt: totals.token_usdt.into(),
+                }
+            })
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Moves the sponsor in the leaderboards after their sponsorship of the event changed
+    /// from `old`.
+    pub(crate) fn internal_update_leaderboards(
+        &mut self,
+        sponser_id: &AccountId,
+        event_id: &EventId,
+        old: &Amount,
+    ) {
+        let new = self
+            .internal_unwrap_balance(sponser_id, event_id)
+            .unwrap_or(Amount {
+                token_near: 0,
+                token_usdt: 0,
+            });
+        let mut rankings = vec![
+            (Ranking::NEAR, old.token_near, new.token_near),
+            (Ranking::USDT, old.token_usdt, new.token_usdt),
+        ];
+        if let Some(event_tiers) = self.event_tiers.get(event_id) {
+            rankings.push((
+                Ranking::Normalized,
+                event_tiers.normalize(old),
+                event_tiers.normalize(&new),
+            ));
+        }
+        for (ranking, old_value, new_value) in rankings {
+            if old_value == new_value {
+                continue;
+            }
+            let key = (event_id.clone(), ranking);
+            let mut leaderboard = self
+                .event_leaderboards
+                .get(&key)
+                .unwrap_or_else(|| new_event_leaderboard(&key));
+            move_in_ranking(&mut leaderboard, sponser_id, old_value, new_value);
+            self.event_leaderboards.insert(&key, &leaderboard);
+        }
+
+        let old_totals = self.sponsor_totals.get(sponser_id).unwrap_or(Amount {
+            token_near: 0,
+            token_usdt: 0,
+        });
+        let totals = Amount {
+            token_near: old_totals
+                .token_near
+                .saturating_sub(old.token_near)
+                .saturating_add(new.token_near),
+            token_usdt: old_totals
+                .token_usdt
+                .saturating_sub(old.token_usdt)
+                .saturating_add(new.token_usdt),
+        };
+        move_in_ranking(
+            &mut self.global_leaderboard_near,
+            sponser_id,
+            old_totals.token_near,
+            totals.token_near,
+        );
+        move_in_ranking(
+            &mut self.global_leaderboard_usdt,
+            sponser_id,
+            old_totals.token_usdt,
+            totals.token_usdt,
+        );
+        if totals.token_near == 0 && totals.token_usdt == 0 {
+            self.sponsor_totals.remove(sponser_id);
+        } else {
+            self.sponsor_totals.insert(sponser_id, &totals);
+        }
+    }
+
+    /// Ranks the sponsors of the event again after the conversion rate of its tiers changed.
+    pub(crate) fn internal_rebuild_normalized_leaderboard(&mut self, event_id: &EventId) {
+        let event_tiers = self.event_tiers.get(event_id).unwrap();
+        let key = (event_id.clone(), Ranking::Normalized);
+        let mut leaderboard = self
+            .event_leaderboards
+            .get(&key)
+            .unwrap_or_else(|| new_event_leaderboard(&key));
+        leaderboard.clear();
+        for sponser_id in self.internal_watch_detail_event(event_id).sponsers {
+            if let Ok(amount) = self.internal_unwrap_balance(&sponser_id, event_id) {
+                move_in_ranking(
+                    &mut leaderboard,
+                    &sponser_id,
+                    0,
+                    event_tiers.normalize(&amount),
+                );
+            }
+        }
+        self.event_leaderboards.insert(&key, &leaderboard);
+    }
+}
+
+fn new_event_leaderboard(key: &(EventId, Ranking)) -> TreeMap<(Balance, AccountId), ()> {
+    TreeMap::new(
+        Prefix::EventLeaderboard {
+            event_hash: env::sha256(key.0.as_bytes()),
+            ranking: key.1.clone(),
+        }
+        .try_to_vec()
+        .unwrap(),
+    )
+}
+
+/// Moves the account from `old` to `new` in the ranking, 0 being out of it.
+fn move_in_ranking(
+    ranking: &mut TreeMap<(Balance, AccountId), ()>,
+    account_id: &AccountId,
+    old: Balance,
+    new: Balance,
+) {
+    if old == new {
+        return;
+    }
+    if old > 0 {
+        ranking.remove(&(old, account_id.clone()));
+    }
+    if new > 0 {
+        ranking.insert(&(new, account_id.clone()), &());
+    }
+}
@@ -0,0 +1,133 @@
+use crate::*;
+
+/// Longest memo a sponsor can attach to a contribution, in bytes.
+pub const MAX_CONTRIBUTION_MEMO_LENGTH: usize = 256;
+/// Smallest USDT sponsorship, 1 USDT: its ledger entry is stored at the contract's expense, as
+/// `ft_transfer_call` can't attach a storage deposit.
+pub const MIN_USDT_CONTRIBUTION: Balance = 1_000_000;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum ContributionKind {
+    /// First sponsorship of the event
+    Initial,
+    TopUp,
+    /// Early withdrawal, or sponsorship handed to another account
+    Withdrawal,
+    /// Claim of a cancelled event
+    Refund,
+}
+
+/// Entry of the contribution ledger of an event. Entries are never modified.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Contribution {
+    pub event_id: EventId,
+    pub sponser_id: AccountId,
+    pub token: Token,
+    /// Amount added to or taken from the sponsorship, net of fees
+    pub amount: U128,
+    pub kind: ContributionKind,
+    /// Timestamp in nanoseconds
+    pub timestamp: U64,
+    pub block_height: U64,
+    pub memo: Option<String>,
+}
+
+#[near_bindgen]
+impl Contract {
+    pub fn get_event_contribution_count(&self, event_id: EventId) -> u64 {
+        self.contribution_counts.get(&event_id).unwrap_or(0)
+    }
+
+    /// Returns the contributions to the event, from the oldest.
+    pub fn get_event_contributions(
+        &self,
+        event_id: EventId,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<Contribution> {
+        let count = self.get_event_contribution_count(event_id.clone());
+        let from_index = from_index.unwrap_or(0);
+        let to_index = std::cmp::min(from_index + limit.unwrap_or(50), count);
+        (from_index..to_index)
+            .map(|index| self.contributions.get(&(event_id.clone(), index)).unwrap())
+            .collect()
+    }
+
+    pub fn get_sponsor_contribution_count(&self, account_id: AccountId) -> u64 {
+        self.sponsor_contribution_counts
+            .get(&account_id)
+            .unwrap_or(0)
+    }
+
+    /// Returns the contributions of `account_id` to every event, from the oldest.
+    pub fn get_sponsor_contributions(
+        &self,
+        account_id: AccountId,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<Contribution> {
+        let count = self.get_sponsor_contribution_count(account_id.clone());
+        let from_index = from_index.unwrap_or(0);
+        let to_index = std::cmp::min(from_index + limit.unwrap_or(50), count);
+        (from_index..to_index)
+            .map(|index| {
+                let key = self
+                    .sponsor_contributions
+                    .get(&(account_id.clone(), index))
+                    .unwrap();
+                self.contributions.get(&key).unwrap()
+            })
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Appends a contribution to the ledgers of the event and of the sponsor.
+    pub(crate) fn internal_record_contribution(
+        &mut self,
+        sponser_id: &AccountId,
+        event_id: &EventId,
+        token: &Token,
+        amount: Balance,
+        kind: ContributionKind,
+        memo: Option<String>,
+    ) {
+        if let Some(memo) = &memo {
+            require!(
+                memo.len() <= MAX_CONTRIBUTION_MEMO_LENGTH,
+                "The memo should be at most 256 bytes"
+            );
+        }
+        if amount == 0 {
+            return;
+        }
+
+        let index = self.contribution_counts.get(event_id).unwrap_or(0);
+        let contribution = Contribution {
+            event_id: event_id.clone(),
+            sponser_id: sponser_id.clone(),
+            token: token.clone(),
+            amount: amount.into(),
+            kind,
+            timestamp: env::block_timestamp().into(),
+            block_height: env::block_height().into(),
+            memo,
+        };
+        self.contributions
+            .insert(&(event_id.clone(), index), &contribution);
+        self.contribution_counts.insert(event_id, &(index + 1));
+
+        let sponser_index = self
+            .sponsor_contribution_counts
+            .get(sponser_id)
+            .unwrap_or(0);
+        self.sponsor_contributions.insert(
+            &(sponser_id.clone(), sponser_index),
+            &(event_id.clone(), index),
+        );
+        self.sponsor_contribution_counts
+            .insert(sponser_id, &(sponser_index + 1));
+    }
+}
@@ -1,25 +1,68 @@
 use external::{ext_ft_storage, ext_self};
 use near_sdk::borsh::{self, BorshDeserialize, BorshSerialize};
-use near_sdk::collections::{LazyOption, LookupMap, UnorderedSet};
-use near_sdk::json_types::U128;
+use near_sdk::collections::{LazyOption, LookupMap, LookupSet, TreeMap, UnorderedSet};
+use near_sdk::json_types::{U128, U64};
 use near_sdk::serde::{Deserialize, Serialize};
 use near_sdk::{
-    env, near_bindgen, AccountId, Balance, BorshStorageKey, Gas, PanicOnDefault, StorageUsage, require,
+    env, near_bindgen, AccountId, Balance, BorshStorageKey, Gas, PanicOnDefault,
+    PublicKey, StorageUsage, require,
 };
+pub mod airdrop;
+pub mod allowance;
+pub mod analytics;
 mod callback;
+pub mod dispute;
 mod event;
 pub mod events;
 mod external;
+pub mod fee;
 pub mod ft_core;
 pub mod internal;
+pub mod leaderboard;
+pub mod ledger;
+pub mod limits;
 pub mod metadata;
+pub mod migrate;
+pub mod milestone;
+pub mod oracle;
+pub mod points;
+pub mod position;
+pub mod prize;
+pub mod quest;
+pub mod raffle;
+pub mod receipt;
+pub mod roles;
 pub mod storage;
+pub mod task;
+#[cfg(test)]
+mod test;
+pub mod tier;
+pub mod treasury;
 mod utils;
+pub mod vesting;
+pub mod withdrawal;
 
+use crate::airdrop::*;
+use crate::analytics::*;
+use crate::dispute::*;
 use crate::events::*;
+use crate::fee::*;
+use crate::leaderboard::*;
+use crate::ledger::*;
+use crate::limits::*;
 use crate::metadata::*;
-
-use std::collections::HashSet;
+use crate::milestone::*;
+use crate::oracle::*;
+use crate::prize::*;
+use crate::quest::*;
+use crate::raffle::*;
+use crate::receipt::*;
+use crate::roles::*;
+use crate::tier::*;
+use crate::vesting::*;
+use crate::withdrawal::*;
+
+use std::collections::{HashMap, HashSet};
 pub const FT_TRANSFER_GAS: Gas = Gas(10_000_000_000_000);
 use event::*;
 use utils::*;
@@ -30,16 +73,37 @@ const DATA_IMAGE_SVG_NEAR_ICON: &str = "data:image/svg+xml;charset=UTF-8,%3c?xml
 /// The specific version of the standard we're using
 pub const FT_METADATA_SPEC: &str = "ft-1.0.0";
 
+/// The USDT contract accepted for sponsorships and task budgets
+pub const USDT_TOKEN_ID: &str = "ft1.tranchinh2001.testnet";
+
 // Task campaign
 pub type TaskId = String;
 pub type ClientAccount = AccountId;
 pub type UserAccount = AccountId;
 pub type EventId = String;
+#[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum TaskStatus {
+    Active,
+    Closed,
+}
+
 #[derive(Serialize, Deserialize, BorshDeserialize, BorshSerialize)]
 #[serde(crate = "near_sdk::serde")]
 pub struct TaskInfo {
     pub client: ClientAccount,
+    /// Token the task is funded and pays its rewards in, PLAT or USDT
+    pub token: Token,
+    /// Remaining budget of the task
     pub amount: U128,
+    /// Amount paid for each completion
+    pub reward: U128,
+    pub max_participants: u64,
+    /// Number of accounts rewarded so far
+    pub participants: u64,
+    /// Timestamp (in nanoseconds) after which no reward can be paid
+    pub expires_at: U64,
+    pub status: TaskStatus,
 }
 
 #[near_bindgen]
@@ -53,6 +117,9 @@ pub struct Contract {
 
     /// Task information
     pub tasks: LookupMap<TaskId, TaskInfo>,
+    pub list_task: UnorderedSet<TaskId>,
+    /// (task, account) pairs that have been rewarded
+    pub task_participants: LookupSet<(TaskId, AccountId)>,
 
     /// The bytes for the largest possible account ID that can be registered on the contract
     pub bytes_for_longest_account_id: StorageUsage,
@@ -67,14 +134,138 @@ pub struct Contract {
     pub client_to_event_id: LookupMap<AccountId, ClientEvent>,
     //sponser -> sponse
     pub sponser_to_sponse: LookupMap<AccountId, Sponse>,
+
+    /// Vesting schedule of each beneficiary, funded from the owner's balance
+    pub vestings: LookupMap<AccountId, VestingSchedule>,
+
+    /// (owner, spender) -> amount the spender can still transfer from the owner's balance
+    pub allowances: LookupMap<(AccountId, AccountId), Balance>,
+
+    /// Merkle airdrop rounds
+    pub airdrops: LookupMap<AirdropRoundId, AirdropRound>,
+    pub next_airdrop_id: AirdropRoundId,
+    /// (round, word index) -> bitmap of the claimed leaves
+    pub airdrop_claimed: LookupMap<(AirdropRoundId, u64), u128>,
+    /// (round, account) pairs that have claimed a leaf
+    pub airdrop_claimers: LookupSet<(AirdropRoundId, AccountId)>,
+
+    /// Backend key authorizing task rewards, and the nonces it already used (shared by all receivers)
+    pub task_signer: Option<PublicKey>,
+    pub used_task_nonces: LookupSet<u64>,
+
+    /// Quests grouping several steps, and the progress of each account on them
+    pub quests: LookupMap<QuestId, Quest>,
+    pub next_quest_id: QuestId,
+    pub quest_progress: LookupMap<(QuestId, AccountId), QuestProgress>,
+
+    /// Non-transferable points ledger: (season, account) -> points
+    pub points: LookupMap<(u32, AccountId), Balance>,
+    pub points_season: u32,
+    /// Ranking of the current season, ordered by (points, account)
+    pub points_ranking: TreeMap<(Balance, AccountId), ()>,
+    pub point_operators: UnorderedSet<AccountId>,
+    /// PLAT paid for each redeemed point
+    pub points_rate: Balance,
+    /// PLAT held by the contract for redemptions
+    pub points_pool: Balance,
+
+    /// Raffles, their participants by registration index, and who has joined them
+    pub raffles: LookupMap<RaffleId, Raffle>,
+    pub next_raffle_id: RaffleId,
+    pub raffle_participants: LookupMap<(RaffleId, u64), AccountId>,
+    pub raffle_entries: LookupSet<(RaffleId, AccountId)>,
+
+    /// Prize distribution of each finished event and its payouts by index
+    pub prize_distributions: LookupMap<EventId, PrizeDistribution>,
+    pub prize_payouts: LookupMap<(EventId, u64), PrizePayout>,
+
+    /// Milestone escrow of each event and the sponsors who voted on each milestone
+    pub milestone_escrows: LookupMap<EventId, MilestoneEscrow>,
+    pub milestone_voters: LookupSet<(EventId, u32, AccountId)>,
+
+    /// Arbitrator of each event, its sponsors' cancellation votes, the arbitrator's rulings
+    /// and the owner's share of a ruling whose transfer failed
+    pub event_arbitrators: LookupMap<EventId, AccountId>,
+    pub cancellation_votes: LookupMap<EventId, CancellationVote>,
+    pub cancellation_voters: LookupSet<(EventId, u32, AccountId)>,
+    pub dispute_rulings: LookupMap<EventId, Ruling>,
+    pub unpaid_rulings: LookupMap<EventId, Amount>,
+
+    /// Account receiving the platform's share of sponsorships
+    pub treasury_id: AccountId,
+    /// Early withdrawal policy of each event, and when each sponsor first sponsored it
+    pub withdrawal_policies: LookupMap<EventId, WithdrawalPolicy>,
+    pub sponsored_at: LookupMap<(EventId, AccountId), u64>,
+    /// Withdrawal penalties kept in the funds of each event
+    pub withdrawal_penalties: LookupMap<EventId, Amount>,
+
+    /// Timestamp after which the refunds of each cancelled event can be swept to the treasury
+    pub claim_deadlines: LookupMap<EventId, u64>,
+
+    /// Platform fee taken on sponsorships in basis points, and the fees of the tokens that override it
+    pub platform_fee_bps: u16,
+    pub token_fees: LookupMap<Token, u16>,
+    /// Whether fees are given back with the refunds of the events cancelled from now on,
+    /// and the cancelled events whose fees are given back
+    pub refund_fees_on_cancel: bool,
+    pub fee_refund_events: LookupSet<EventId>,
+    /// Fees of each event, of each sponsorship, and earned in each token
+    pub event_fees: LookupMap<EventId, EventFees>,
+    pub sponser_fees: LookupMap<(EventId, AccountId), Amount>,
+    pub total_fees: LookupMap<Token, Balance>,
+    /// Settled fees and withdrawal penalties the treasurer can withdraw, by token
+    pub treasury_balances: LookupMap<Token, Balance>,
+
+    /// Sponsor tiers of each event
+    pub event_tiers: LookupMap<EventId, EventTiers>,
+
+    /// Sponsorship receipt NFTs, all receipts in circulation and the receipts of each owner
+    pub receipts: LookupMap<ReceiptId, Receipt>,
+    pub receipt_ids: UnorderedSet<ReceiptId>,
+    pub receipts_per_owner: LookupMap<AccountId, UnorderedSet<ReceiptId>>,
+    /// Events whose receipts can be transferred, the others are soulbound
+    pub transferable_receipts: LookupSet<EventId>,
+
+    /// Sponsorship limits of each event, and the (event, account) pairs of their allowlists
+    pub sponsorship_limits: LookupMap<EventId, SponsorshipLimits>,
+    pub sponsor_allowlists: LookupSet<(EventId, AccountId)>,
+
+    /// Co-organizers of each event with their role, and the owners proposed for each event
+    pub event_managers: LookupMap<EventId, HashMap<AccountId, EventRole>>,
+    pub pending_event_owners: LookupMap<EventId, AccountId>,
+
+    /// Contribution ledger of each event by index, and the (event, index) of each sponsor's contributions
+    pub contributions: LookupMap<(EventId, u64), Contribution>,
+    pub contribution_counts: LookupMap<EventId, u64>,
+    pub sponsor_contributions: LookupMap<(AccountId, u64), (EventId, u64)>,
+    pub sponsor_contribution_counts: LookupMap<AccountId, u64>,
+
+    /// Running aggregates: over the platform, by event owner, and by event
+    pub platform_stats: PlatformStats,
+    pub owner_stats: LookupMap<AccountId, OwnerStats>,
+    pub event_activity: LookupMap<EventId, EventActivity>,
+    pub known_sponsors: LookupSet<AccountId>,
+    /// Events ranked by (total, event) in each token
+    pub largest_events_near: TreeMap<(Balance, EventId), ()>,
+    pub largest_events_usdt: TreeMap<(Balance, EventId), ()>,
+
+    /// Sponsors ranked by (sponsorship, sponsor), per event and over every event
+    pub event_leaderboards: LookupMap<(EventId, Ranking), TreeMap<(Balance, AccountId), ()>>,
+    pub sponsor_totals: LookupMap<AccountId, Amount>,
+    pub global_leaderboard_near: TreeMap<(Balance, AccountId), ()>,
+    pub global_leaderboard_usdt: TreeMap<(Balance, AccountId), ()>,
+
+    /// Oracle the sponsorships are valued in USD with, and the prices cached from it
+    pub price_oracle: Option<PriceOracleConfig>,
+    pub token_prices: LookupMap<Token, CachedPrice>,
 }
 
-/// Helper structure for keys of the persistent collections.
+/// Helper structure for keys of the persistent collections of the token itself.
+/// Every other collection takes its prefix from `Prefix`.
 #[derive(BorshSerialize)]
 pub enum StorageKey {
     Accounts,
     Metadata,
-    Tasks,
 }
 
 #[derive(BorshSerialize, BorshStorageKey)]
@@ -83,6 +274,67 @@ pub enum Prefix {
     Events,
     SponserToSponse,
     ClientToEventId,
+    Vestings,
+    Allowances,
+    Airdrops,
+    AirdropClaimed,
+    AirdropClaimers,
+    UsedTaskNonces,
+    ListTask,
+    TaskParticipants,
+    Tasks,
+    Quests,
+    QuestProgress,
+    Points,
+    PointOperators,
+    PointsRanking { season: u32 },
+    Raffles,
+    RaffleParticipants,
+    RaffleEntries,
+    PrizeDistributions,
+    PrizePayouts,
+    MilestoneEscrows,
+    MilestoneVoters,
+    EventArbitrators,
+    CancellationVotes,
+    CancellationVoters,
+    DisputeRulings,
+    UnpaidRulings,
+    WithdrawalPolicies,
+    SponsoredAt,
+    WithdrawalPenalties,
+    ClaimDeadlines,
+    TokenFees,
+    EventFees,
+    SponserFees,
+    TotalFees,
+    TreasuryBalances,
+    FeeRefundEvents,
+    EventTiers,
+    Receipts,
+    ReceiptIds,
+    ReceiptsPerOwner,
+    ReceiptsOfOwner { account_hash: Vec<u8> },
+    TransferableReceipts,
+    SponsorshipLimits,
+    SponsorAllowlists,
+    EventManagers,
+    PendingEventOwners,
+    Contributions,
+    ContributionCounts,
+    SponsorContributions,
+    SponsorContributionCounts,
+    OwnerStats,
+    EventActivity,
+    KnownSponsors,
+    LargestEventsNear,
+    LargestEventsUsdt,
+    EventLeaderboards,
+    EventLeaderboard { event_hash: Vec<u8>, ranking: Ranking },
+    SponsorTotals,
+    GlobalLeaderboardNear,
+    GlobalLeaderboardUsdt,
+    TokenPrices,
 }
 
 #[near_bindgen]
@@ -112,21 +364,7 @@ impl Contract {
     #[init]
     pub fn new(owner_id: AccountId, total_supply: U128, metadata: FungibleTokenMetadata) -> Self {
         // Create a variable of type Self with all the fields initialized.
-        let mut this = Self {
-            // Set the total supply
-            total_supply: total_supply.0,
-            // Set the bytes for the longest account ID to 0 temporarily until it's calculated later
-            bytes_for_longest_account_id: 0,
-            // Storage keys are simply the prefixes used for the collections. This helps avoid data collision
-            accounts: LookupMap::new(StorageKey::Accounts.try_to_vec().unwrap()),
-            metadata: LazyOption::new(StorageKey::Metadata.try_to_vec().unwrap(), Some(&metadata)),
-            tasks: LookupMap::new(StorageKey::Tasks.try_to_vec().unwrap()),
-            owner_id: owner_id.clone(),
-            events: LookupMap::new(Prefix::Events.try_to_vec().unwrap()),
-            sponser_to_sponse: LookupMap::new(Prefix::SponserToSponse.try_to_vec().unwrap()),
-            client_to_event_id: LookupMap::new(Prefix::ClientToEventId.try_to_vec().unwrap()),
-            list_event: UnorderedSet::new(Prefix::ListEvent.try_to_vec().unwrap()),
-        };
+        let mut this = Self::internal_new_state(&owner_id, total_supply.0, &metadata);
 
         // Measure the bytes for the longest account ID and store it in the contract.
         this.measure_bytes_for_longest_account_id();
@@ -159,7 +397,7 @@ impl Contract {
     pub fn active_usdt(&mut self) {
         let attached_deposit = env::attached_deposit();
         assert_fee_storage_deposit();
-        ext_ft_storage::ext("ft1.tranchinh2001.testnet".parse().unwrap())
+        ext_ft_storage::ext(USDT_TOKEN_ID.parse().unwrap())
             .with_attached_deposit(attached_deposit)
             .with_static_gas(FT_TRANSFER_GAS)
             .storage_deposit(Some(env::current_account_id()), None)
@@ -200,37 +438,50 @@ impl Contract {
 
         self.list_event.insert(&event_id);
         self.events.insert(&event_id, &event);
+        self.internal_track_new_event(&event);
         event
     }
 
     #[payable]
-    pub fn sponse_native(&mut self, event_id: EventId, amount: U128) {
+    pub fn sponse_native(&mut self, event_id: EventId, amount: U128, memo: Option<String>) {
         if self.check_exist_event(&event_id) {
+            let init_storage = env::storage_usage();
             assert_at_least_one_yocto();
             let amount: u128 = amount.into();
             let sender_id = env::signer_account_id();
             let attached_deposit = env::attached_deposit();
             require!(
-                attached_deposit == amount,
-                "The attached_deposit must equal to the amount"
+                attached_deposit >= amount,
+                "The attached_deposit must cover the amount"
             );
-            self.internal_sponse(&sender_id, &event_id, amount, Token::NEAR);
+            let accepted =
+                self.internal_limit_sponsorship(&sender_id, &event_id, &Token::NEAR, amount);
+            self.internal_sponse(&sender_id, &event_id, accepted, Token::NEAR, memo);
+            // the part over the limits of the event goes back to the sponsor with the rest of the
+            // deposit that the storage didn't use
+            refund_deposit_over(init_storage, accepted);
         } else {
             env::panic_str("EventId not exist");
         }
     }
 
     #[payable]
-    pub fn more_sponse_native(&mut self, event_id: EventId, amount: U128) {
+    pub fn more_sponse_native(&mut self, event_id: EventId, amount: U128, memo: Option<String>) {
         if self.check_exist_event(&event_id) {
+            let init_storage = env::storage_usage();
             let amount: u128 = amount.into();
             let sender_id = env::signer_account_id();
             let attached_deposit = env::attached_deposit();
             require!(
-                attached_deposit == amount,
-                "The attached_deposit must equal to the amount"
+                attached_deposit >= amount,
+                "The attached_deposit must cover the amount"
             );
-            self.internal_more_sponse_near(&sender_id, &event_id, amount);
+            let accepted =
+                self.internal_limit_sponsorship(&sender_id, &event_id, &Token::NEAR, amount);
+            self.internal_more_sponse_near(&sender_id, &event_id, accepted, memo);
+            // the part over the limits of the event goes back to the sponsor with the rest of the
+            // deposit that the storage didn't use
+            refund_deposit_over(init_storage, accepted);
         } else {
             env::panic_str("EventId not exist");
         }
@@ -241,7 +492,11 @@ impl Contract {
             if env::signer_account_id() == self.owner_id {
                 match self.events.get(&event_id) {
                     Some(mut res) => {
-                        res.status = Status::Finish;
+                        require!(
+                            res.status == Status::Active,
+                            "Only an active event can be finished"
+                        );
+                        self.internal_set_event_status(&mut res, Status::Finish);
                         self.events.insert(&event_id, &res);
                     }
                     None => {
@@ -259,10 +514,33 @@ impl Contract {
         match self.events.get(event_id) {
             Some(res) => {
                 if res.status == Status::Cancel {
+                    let init_storage = env::storage_usage();
                     assert_at_least_one_yocto();
+                    self.assert_claim_open(event_id);
                     let receiver_id = env::signer_account_id();
                     match self.internal_unwrap_balance(&receiver_id, event_id) {
                         Ok(amount) => {
+                            let sponsored = (amount.token_near, amount.token_usdt);
+                            // only what hasn't been released through milestones is refunded
+                            let amount = Amount {
+                                token_near: self
+                                    .internal_refundable_amount(event_id, amount.token_near),
+                                token_usdt: self
+                                    .internal_refundable_amount(event_id, amount.token_usdt),
+                            };
+                            for (token, refund) in [
+                                (Token::NEAR, amount.token_near),
+                                (Token::USDT, amount.token_usdt),
+                            ] {
+                                self.internal_record_contribution(
+                                    &receiver_id,
+                                    event_id,
+                                    &token,
+                                    refund,
+                                    ContributionKind::Refund,
+                                    None,
+                                );
+                            }
                             if amount.token_near > 0 {
                                 self.claim_token_near(
                                     &receiver_id,
@@ -277,11 +555,20 @@ impl Contract {
                                     event_id.clone(),
                                 );
                             }
+                            self.internal_refund_fees(&receiver_id, event_id);
+                            // the receipt stays as proof of the part that went to the event
+                            if sponsored == (amount.token_near, amount.token_usdt) {
+                                self.internal_burn_receipt(
+                                    &receiver_id,
+                                    event_id,
+                                    "Sponsorship refunded",
+                                );
+                            }
                             self.handle_sponser_claim(receiver_id, event_id.clone());
                         }
                         Err(_) => env::panic_str("You havn't sponse this event yet"),
                     }
-                    // refund_deposit(init_storage);
+                    refund_deposit(init_storage);
                 } else {
                     env::panic_str("This event has not been canceled so you cannot withdraw token");
                 }
@@ -296,11 +583,15 @@ impl Contract {
         if self.check_exist_event(&event_id) {
             assert_at_least_one_yocto();
             require!(
-                self.check_owner_event(&event_id),
+                self.check_event_role(&event_id, EventRole::Admin),
                 "You are not allowed to cancel"
             );
             let mut event = self.events.get(&event_id).unwrap();
-            event.status = Status::Cancel;
+            require!(
+                event.status == Status::Active,
+                "Only an active event can be cancelled"
+            );
+            self.internal_cancel_event(&mut event);
             self.events.insert(&event_id, &event);
         } else {
             env::panic_str("EventId not exist");
@@ -345,3 +636,98 @@ impl Contract {
         self.internal_watch_detail_event(event_id)
     }
 }
+
+impl Contract {
+    /// State of a freshly deployed contract: every collection is empty.
+    pub(crate) fn internal_new_state(
+        owner_id: &AccountId,
+        total_supply: Balance,
+        metadata: &FungibleTokenMetadata,
+    ) -> Self {
+        Self {
+            // Set the total supply
+            total_supply,
+            // Set the bytes for the longest account ID to 0 temporarily until it's calculated later
+            bytes_for_longest_account_id: 0,
+            // Storage keys are simply the prefixes used for the collections. This helps avoid data collision
+            accounts: LookupMap::new(StorageKey::Accounts.try_to_vec().unwrap()),
+            metadata: LazyOption::new(StorageKey::Metadata.try_to_vec().unwrap(), Some(metadata)),
+            tasks: LookupMap::new(Prefix::Tasks.try_to_vec().unwrap()),
+            list_task: UnorderedSet::new(Prefix::ListTask.try_to_vec().unwrap()),
+            task_participants: LookupSet::new(Prefix::TaskParticipants.try_to_vec().unwrap()),
+            owner_id: owner_id.clone(),
+            events: LookupMap::new(Prefix::Events.try_to_vec().unwrap()),
+            sponser_to_sponse: LookupMap::new(Prefix::SponserToSponse.try_to_vec().unwrap()),
+            client_to_event_id: LookupMap::new(Prefix::ClientToEventId.try_to_vec().unwrap()),
+            list_event: UnorderedSet::new(Prefix::ListEvent.try_to_vec().unwrap()),
+            vestings: LookupMap::new(Prefix::Vestings.try_to_vec().unwrap()),
+            allowances: LookupMap::new(Prefix::Allowances.try_to_vec().unwrap()),
+            airdrops: LookupMap::new(Prefix::Airdrops.try_to_vec().unwrap()),
+            next_airdrop_id: 0,
+            airdrop_claimed: LookupMap::new(Prefix::AirdropClaimed.try_to_vec().unwrap()),
+            task_signer: None,
+            used_task_nonces: LookupSet::new(Prefix::UsedTaskNonces.try_to_vec().unwrap()),
+            quests: LookupMap::new(Prefix::Quests.try_to_vec().unwrap()),
+            next_quest_id: 0,
+            quest_progress: LookupMap::new(Prefix::QuestProgress.try_to_vec().unwrap()),
+            points: LookupMap::new(Prefix::Points.try_to_vec().unwrap()),
+            points_season: 0,
+            points_ranking: TreeMap::new(Prefix::PointsRanking { season: 0 }.try_to_vec().unwrap()),
+            point_operators: UnorderedSet::new(Prefix::PointOperators.try_to_vec().unwrap()),
+            points_rate: 0,
+            points_pool: 0,
+            raffles: LookupMap::new(Prefix::Raffles.try_to_vec().unwrap()),
+            next_raffle_id: 0,
+            raffle_participants: LookupMap::new(Prefix::RaffleParticipants.try_to_vec().unwrap()),
+            raffle_entries: LookupSet::new(Prefix::RaffleEntries.try_to_vec().unwrap()),
+            prize_distributions: LookupMap::new(Prefix::PrizeDistributions.try_to_vec().unwrap()),
+            prize_payouts: LookupMap::new(Prefix::PrizePayouts.try_to_vec().unwrap()),
+            milestone_escrows: LookupMap::new(Prefix::MilestoneEscrows.try_to_vec().unwrap()),
+            milestone_voters: LookupSet::new(Prefix::MilestoneVoters.try_to_vec().unwrap()),
+            event_arbitrators: LookupMap::new(Prefix::EventArbitrators.try_to_vec().unwrap()),
+            cancellation_votes: LookupMap::new(Prefix::CancellationVotes.try_to_vec().unwrap()),
+            cancellation_voters: LookupSet::new(Prefix::CancellationVoters.try_to_vec().unwrap()),
+            dispute_rulings: LookupMap::new(Prefix::DisputeRulings.try_to_vec().unwrap()),
+            unpaid_rulings: LookupMap::new(Prefix::UnpaidRulings.try_to_vec().unwrap()),
+            treasury_id: owner_id.clone(),
+            withdrawal_policies: LookupMap::new(Prefix::WithdrawalPolicies.try_to_vec().unwrap()),
+            sponsored_at: LookupMap::new(Prefix::SponsoredAt.try_to_vec().unwrap()),
+            claim_deadlines: LookupMap::new(Prefix::ClaimDeadlines.try_to_vec().unwrap()),
+            platform_fee_bps: 0,
+            token_fees: LookupMap::new(Prefix::TokenFees.try_to_vec().unwrap()),
+            refund_fees_on_cancel: false,
+            fee_refund_events: LookupSet::new(Prefix::FeeRefundEvents.try_to_vec().unwrap()),
+            event_fees: LookupMap::new(Prefix::EventFees.try_to_vec().unwrap()),
+            sponser_fees: LookupMap::new(Prefix::SponserFees.try_to_vec().unwrap()),
+            total_fees: LookupMap::new(Prefix::TotalFees.try_to_vec().unwrap()),
+            treasury_balances: LookupMap::new(Prefix::TreasuryBalances.try_to_vec().unwrap()),
+            event_tiers: LookupMap::new(Prefix::EventTiers.try_to_vec().unwrap()),
+            receipts: LookupMap::new(Prefix::Receipts.try_to_vec().unwrap()),
+            receipt_ids: UnorderedSet::new(Prefix::ReceiptIds.try_to_vec().unwrap()),
+            receipts_per_owner: LookupMap::new(Prefix::ReceiptsPerOwner.try_to_vec().unwrap()),
+            transferable_receipts: LookupSet::new(Prefix::TransferableReceipts.try_to_vec().unwrap()),
+            sponsorship_limits: LookupMap::new(Prefix::SponsorshipLimits.try_to_vec().unwrap()),
+            sponsor_allowlists: LookupSet::new(Prefix::SponsorAllowlists.try_to_vec().unwrap()),
+            event_managers: LookupMap::new(Prefix::EventManagers.try_to_vec().unwrap()),
+            pending_event_owners: LookupMap::new(Prefix::PendingEventOwners.try_to_vec().unwrap()),
+            contributions: LookupMap::new(Prefix::Contributions.try_to_vec().unwrap()),
+            contribution_counts: LookupMap::new(Prefix::ContributionCounts.try_to_vec().unwrap()),
+            sponsor_contributions: LookupMap::new(Prefix::SponsorContributions.try_to_vec().unwrap()),
+            sponsor_contribution_counts: LookupMap::new(Prefix::SponsorContributionCounts.try_to_vec().unwrap()),
+            platform_stats: PlatformStats::default(),
+            owner_stats: LookupMap::new(Prefix::OwnerStats.try_to_vec().unwrap()),
+            event_activity: LookupMap::new(Prefix::EventActivity.try_to_vec().unwrap()),
+            known_sponsors: LookupSet::new(Prefix::KnownSponsors.try_to_vec().unwrap()),
+            largest_events_near: TreeMap::new(Prefix::LargestEventsNear.try_to_vec().unwrap()),
+            largest_events_usdt: TreeMap::new(Prefix::LargestEventsUsdt.try_to_vec().unwrap()),
+            event_leaderboards: LookupMap::new(Prefix::EventLeaderboards.try_to_vec().unwrap()),
+            sponsor_totals: LookupMap::new(Prefix::SponsorTotals.try_to_vec().unwrap()),
+            global_leaderboard_near: TreeMap::new(Prefix::GlobalLeaderboardNear.try_to_vec().unwrap()),
+            global_leaderboard_usdt: TreeMap::new(Prefix::GlobalLeaderboardUsdt.try_to_vec().unwrap()),
+            price_oracle: None,
+            token_prices: LookupMap::new(Prefix::TokenPrices.try_to_vec().unwrap()),
+            withdrawal_penalties: LookupMap::new(Prefix::WithdrawalPenalties.try_to_vec().unwrap()),
+            airdrop_claimers: LookupSet::new(Prefix::AirdropClaimers.try_to_vec().unwrap()),
+        }
+    }
+}
@@ -0,0 +1,188 @@
+use crate::*;
+
+/// Bounds of the sponsorships of an event in one token.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenLimits {
+    /// Smallest total a sponsor can contribute
+    pub min_contribution: Option<U128>,
+    /// Largest total a sponsor can contribute
+    pub max_contribution: Option<U128>,
+    /// Largest total the event can raise
+    pub hard_cap: Option<U128>,
+}
+
+/// Who can sponsor an event and how much.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SponsorshipLimits {
+    /// Only accounts of the allowlist of the event can sponsor it
+    pub allowlist_only: bool,
+    pub near: TokenLimits,
+    pub usdt: TokenLimits,
+}
+
+impl SponsorshipLimits {
+    pub fn token(&self, token: &Token) -> &TokenLimits {
+        match token {
+            Token::NEAR => &self.near,
+            _ => &self.usdt,
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the limits of the sponsorships of the event, or removes them. They only apply to
+    /// the next sponsorships.
+    /// The attached deposit covers the storage of the limits, the rest is refunded.
+    #[payable]
+    pub fn set_sponsorship_limits(&mut self, event_id: EventId, limits: Option<SponsorshipLimits>) {
+        let init_storage = env::storage_usage();
+        self.internal_watch_detail_event(&event_id);
+        require!(
+            self.check_owner_event(&event_id),
+            "Only the owner of the event can set its sponsorship limits"
+        );
+        match limits {
+            Some(limits) => {
+                for token_limits in [&limits.near, &limits.usdt] {
+                    if let (Some(min), Some(max)) =
+                        (token_limits.min_contribution, token_limits.max_contribution)
+                    {
+                        require!(
+                            min.0 <= max.0,
+                            "The minimum contribution should be at most the maximum"
+                        );
+                    }
+                }
+                self.sponsorship_limits.insert(&event_id, &limits);
+            }
+            None => {
+                self.sponsorship_limits.remove(&event_id);
+            }
+        }
+        refund_deposit(init_storage);
+    }
+
+    /// Adds accounts to the allowlist of the event.
+    /// The attached deposit covers the storage of the allowlist, the rest is refunded.
+    #[payable]
+    pub fn add_sponsor_allowlist(&mut self, event_id: EventId, account_ids: Vec<AccountId>) {
+        let init_storage = env::storage_usage();
+        self.internal_watch_detail_event(&event_id);
+        require!(
+            self.check_owner_event(&event_id),
+            "Only the owner of the event can change its allowlist"
+        );
+        for account_id in account_ids {
+            self.sponsor_allowlists
+                .insert(&(event_id.clone(), account_id));
+        }
+        refund_deposit(init_storage);
+    }
+
+    /// Removes accounts from the allowlist of the event. Their current sponsorships are kept.
+    pub fn remove_sponsor_allowlist(&mut self, event_id: EventId, account_ids: Vec<AccountId>) {
+        self.internal_watch_detail_event(&event_id);
+        require!(
+            self.check_owner_event(&event_id),
+            "Only the owner of the event can change its allowlist"
+        );
+        for account_id in account_ids {
+            self.sponsor_allowlists
+                .remove(&(event_id.clone(), account_id));
+        }
+    }
+
+    pub fn get_sponsorship_limits(&self, event_id: EventId) -> Option<SponsorshipLimits> {
+        self.sponsorship_limits.get(&event_id)
+    }
+
+    /// Whether `account_id` can sponsor the event.
+    pub fn is_sponsor_allowed(&self, event_id: EventId, account_id: AccountId) -> bool {
+        match self.sponsorship_limits.get(&event_id) {
+            Some(limits) if limits.allowlist_only => {
+                self.sponsor_allowlists.contains(&(event_id, account_id))
+            }
+            _ => true,
+        }
+    }
+}
+
+impl Contract {
+    /// Returns the part of a sponsorship of `amount` that fits in the limits of the event, the
+    /// rest is to be refunded to the sponsor. The maximum and the hard cap are never exceeded.
+    pub(crate) fn internal_limit_sponsorship(
+        &self,
+        sponser_id: &AccountId,
+        event_id: &EventId,
+        token: &Token,
+        amount: Balance,
+    ) -> Balance {
+        let limits = match self.sponsorship_limits.get(event_id) {
+            Some(limits) => limits,
+            None => return amount,
+        };
+        self.assert_sponsor_allowed(event_id, sponser_id);
+
+        let event = self.internal_watch_detail_event(event_id);
+        let contribution = self
+            .internal_unwrap_balance(sponser_id, event_id)
+            .map(|balance| match token {
+                Token::NEAR => balance.token_near,
+                _ => balance.token_usdt,
+            })
+            .unwrap_or(0);
+        let total = match token {
+            Token::NEAR => event.total_near,
+            _ => event.total_usdt,
+        };
+        let token_limits = limits.token(token);
+
+        let mut accepted = amount;
+        if let Some(max) = token_limits.max_contribution {
+            accepted = accepted.min(max.0.saturating_sub(contribution));
+        }
+        if let Some(hard_cap) = token_limits.hard_cap {
+            accepted = accepted.min(hard_cap.0.saturating_sub(total));
+        }
+        require!(
+            accepted > 0,
+            "The sponsorship limit of the event is reached"
+        );
+        if let Some(min) = token_limits.min_contribution {
+            require!(
+                contribution + accepted >= min.0,
+                "The contribution is below the minimum of the event"
+            );
+        }
+        accepted
+    }
+
+    pub(crate) fn assert_sponsor_allowed(&self, event_id: &EventId, account_id: &AccountId) {
+        require!(
+            self.is_sponsor_allowed(event_id.clone(), account_id.clone()),
+            "You are not on the allowlist of this event"
+        );
+    }
+
+    /// Checks that the sponsorship `amount` of an account stays within the maximums of the event.
+    pub(crate) fn assert_within_max_contribution(&self, event_id: &EventId, amount: &Amount) {
+        let limits = match self.sponsorship_limits.get(event_id) {
+            Some(limits) => limits,
+            None => return,
+        };
+        for (token_limits, contribution) in [
+            (&limits.near, amount.token_near),
+            (&limits.usdt, amount.token_usdt),
+        ] {
+            if let Some(max) = token_limits.max_contribution {
+                require!(
+                    contribution <= max.0,
+                    "The contribution is above the maximum of the event"
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use crate::*;
+
+/// Layout of the contract state before the sponsorship, task and token features were added.
+#[derive(BorshDeserialize, BorshSerialize)]
+pub struct OldContract {
+    pub accounts: LookupMap<AccountId, Balance>,
+    pub total_supply: Balance,
+    /// Never written to, its prefix is the one of `sponser_to_sponse`
+    pub tasks: LookupMap<TaskId, TaskInfo>,
+    pub bytes_for_longest_account_id: StorageUsage,
+    pub metadata: LazyOption<FungibleTokenMetadata>,
+    pub owner_id: AccountId,
+    pub events: LookupMap<EventId, Event>,
+    pub list_event: UnorderedSet<EventId>,
+    pub client_to_event_id: LookupMap<AccountId, ClientEvent>,
+    pub sponser_to_sponse: LookupMap<AccountId, Sponse>,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Upgrades the state of a contract deployed before the new features, to be called in the
+    /// same transaction as the deployment of the new code. The balances, events and sponsorships
+    /// are kept, the analytics, the largest events and the leaderboards are rebuilt from the
+    /// events, and every other new collection starts empty.
+    #[private]
+    #[init(ignore_state)]
+    pub fn migrate() -> Self {
+        let old: OldContract =
+            env::state_read().unwrap_or_else(|| env::panic_str("The contract is not initialized"));
+        let metadata = old.metadata.get().unwrap();
+        let mut this = Self::internal_new_state(&old.owner_id, old.total_supply, &metadata);
+        this.accounts = old.accounts;
+        this.bytes_for_longest_account_id = old.bytes_for_longest_account_id;
+        this.metadata = old.metadata;
+        this.events = old.events;
+        this.list_event = old.list_event;
+        this.client_to_event_id = old.client_to_event_id;
+        this.sponser_to_sponse = old.sponser_to_sponse;
+
+        // The analytics and leaderboards are rebuilt from the events
+        for event_id in this.list_event.to_vec() {
+            let event = this.events.get(&event_id).unwrap();
+            this.internal_track_new_event(&event);
+            this.internal_on_total_change(&event, &Token::NEAR, 0);
+            this.internal_on_total_change(&event, &Token::USDT, 0);
+            for sponser_id in event.sponsers.iter() {
+                this.internal_track_sponsor(sponser_id);
+                let no_amount = Amount {
+                    token_near: 0,
+                    token_usdt: 0,
+                };
+                this.internal_update_leaderboards(sponser_id, &event_id, &no_amount);
+            }
+        }
+        this
+    }
+}
@@ -0,0 +1,365 @@
+use crate::external::ext_self;
+use crate::prize::BPS_DENOMINATOR;
+use crate::*;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum MilestoneStatus {
+    Pending,
+    Voting,
+    Released,
+    Rejected,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Milestone {
+    pub name: String,
+    /// Share of the event funds released with this milestone, in basis points
+    pub share_bps: u16,
+    pub status: MilestoneStatus,
+}
+
+/// Votes on the release of the current milestone. Each sponsor weighs what they deposited in each token.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MilestoneVote {
+    pub milestone: u32,
+    /// Timestamp (in nanoseconds) after which the release is rejected if it wasn't approved
+    pub deadline: U64,
+    pub approve_near: U128,
+    pub approve_usdt: U128,
+    pub reject_near: U128,
+    pub reject_usdt: U128,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct MilestoneEscrow {
+    pub milestones: Vec<Milestone>,
+    /// Share of the sponsored amount, in basis points, that must approve a release in each token
+    pub threshold_bps: u16,
+    /// How long sponsors have to vote on a release, in nanoseconds
+    pub voting_period: U64,
+    /// Index of the next milestone to request
+    pub next_milestone: u32,
+    /// Share of the event funds released to the owner so far, in basis points
+    pub released_bps: u16,
+    pub vote: Option<MilestoneVote>,
+    /// Released amounts whose transfer to the owner failed
+    pub unpaid_near: U128,
+    pub unpaid_usdt: U128,
+}
+
+impl MilestoneVote {
+    /// Whether the approvals reach the threshold of the voting totals in every token.
+    pub fn is_approved(&self, totals: &Amount, threshold_bps: u16) -> bool {
+        let threshold = threshold_bps as u128;
+        [
+            (self.approve_near.0, totals.token_near),
+            (self.approve_usdt.0, totals.token_usdt),
+        ]
+        .iter()
+        .all(|(votes, total)| votes * BPS_DENOMINATOR >= total * threshold)
+    }
+
+    /// Whether the rejections make the threshold unreachable in one of the tokens.
+    pub fn is_rejected(&self, totals: &Amount, threshold_bps: u16) -> bool {
+        let threshold = threshold_bps as u128;
+        [
+            (self.reject_near.0, totals.token_near),
+            (self.reject_usdt.0, totals.token_usdt),
+        ]
+        .iter()
+        .any(|(votes, total)| votes * BPS_DENOMINATOR > total * (BPS_DENOMINATOR - threshold))
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Splits the funds of an active event into milestones released one by one with the sponsors' approval.
+    /// `milestones` are (name, share in basis points) pairs whose shares add up to 100%.
+    /// The attached deposit covers the storage of the escrow, the rest is refunded.
+    #[payable]
+    pub fn set_event_milestones(
+        &mut self,
+        event_id: EventId,
+        milestones: Vec<(String, u16)>,
+        threshold_bps: u16,
+        voting_period: U64,
+    ) -> MilestoneEscrow {
+        let init_storage = env::storage_usage();
+        let event = self.internal_watch_detail_event(&event_id);
+        require!(
+            self.check_owner_event(&event_id),
+            "Only the owner of the event can set its milestones"
+        );
+        require!(event.status == Status::Active, "The event is not active");
+        require!(
+            !self.milestone_escrows.contains_key(&event_id),
+            "The milestones of this event are already set"
+        );
+        require!(!milestones.is_empty(), "No milestones");
+        require!(
+            milestones
+                .iter()
+                .map(|(_, share_bps)| *share_bps as u128)
+                .sum::<u128>()
+                == BPS_DENOMINATOR,
+            "The shares of the milestones should add up to 10000 basis points"
+        );
+        require!(
+            threshold_bps > 0 && threshold_bps as u128 <= BPS_DENOMINATOR,
+            "The approval threshold should be between 1 and 10000 basis points"
+        );
+        require!(
+            voting_period.0 > 0,
+            "The voting period should be a positive number"
+        );
+
+        let escrow = MilestoneEscrow {
+            milestones: milestones
+                .into_iter()
+                .map(|(name, share_bps)| Milestone {
+                    name,
+                    share_bps,
+                    status: MilestoneStatus::Pending,
+                })
+                .collect(),
+            threshold_bps,
+            voting_period,
+            next_milestone: 0,
+            released_bps: 0,
+            vote: None,
+            unpaid_near: U128(0),
+            unpaid_usdt: U128(0),
+        };
+        self.milestone_escrows.insert(&event_id, &escrow);
+
+        refund_deposit(init_storage);
+        escrow
+    }
+
+    /// Opens the sponsors' vote on the release of the next milestone.
+    /// Once a release has been requested, the event doesn't accept new sponsorships.
+    pub fn request_milestone_release(&mut self, event_id: EventId) -> MilestoneVote {
+        let event = self.internal_watch_detail_event(&event_id);
+        require!(
+            self.check_event_role(&event_id, EventRole::Finance),
+            "You are not allowed to request a release of this event"
+        );
+        require!(event.status == Status::Active, "The event is not active");
+        let mut escrow = self.internal_unwrap_milestone_escrow(&event_id);
+        require!(escrow.vote.is_none(), "A release is already being voted");
+        let index = escrow.next_milestone;
+        require!(
+            (index as usize) < escrow.milestones.len(),
+            "Every milestone has already been released"
+        );
+
+        escrow.milestones[index as usize].status = MilestoneStatus::Voting;
+        let vote = MilestoneVote {
+            milestone: index,
+            deadline: U64(env::block_timestamp() + escrow.voting_period.0),
+            approve_near: U128(0),
+            approve_usdt: U128(0),
+            reject_near: U128(0),
+            reject_usdt: U128(0),
+        };
+        escrow.vote = Some(vote);
+        self.milestone_escrows.insert(&event_id, &escrow);
+        escrow.vote.unwrap()
+    }
+
+    /// Approves or rejects the requested release with the weight of the caller's sponsorship.
+    /// The release is settled as soon as the votes reach the threshold either way.
+    /// The attached deposit covers the storage of the vote, the rest is refunded.
+    #[payable]
+    pub fn vote_milestone(&mut self, event_id: EventId, approve: bool) -> MilestoneStatus {
+        let init_storage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+        require!(
+            self.internal_watch_detail_event(&event_id).status == Status::Active,
+            "The event is not active"
+        );
+        let mut escrow = self.internal_unwrap_milestone_escrow(&event_id);
+        let mut vote = escrow
+            .vote
+            .take()
+            .unwrap_or_else(|| env::panic_str("No release is being voted"));
+        require!(
+            env::block_timestamp() <= vote.deadline.0,
+            "The vote on this release is over"
+        );
+        let weight = self
+            .internal_unwrap_balance(&account_id, &event_id)
+            .unwrap_or_else(|_| env::panic_str("Only sponsors of the event can vote"));
+        require!(
+            self.milestone_voters
+                .insert(&(event_id.clone(), vote.milestone, account_id)),
+            "You have already voted on this release"
+        );
+
+        if approve {
+            vote.approve_near = U128(vote.approve_near.0 + weight.token_near);
+            vote.approve_usdt = U128(vote.approve_usdt.0 + weight.token_usdt);
+        } else {
+            vote.reject_near = U128(vote.reject_near.0 + weight.token_near);
+            vote.reject_usdt = U128(vote.reject_usdt.0 + weight.token_usdt);
+        }
+
+        let totals = self.internal_voting_totals(&event_id);
+        let status = if vote.is_approved(&totals, escrow.threshold_bps) {
+            self.internal_release_milestone(&event_id, &mut escrow, vote.milestone);
+            MilestoneStatus::Released
+        } else if vote.is_rejected(&totals, escrow.threshold_bps) {
+            self.internal_reject_milestone(&event_id, &mut escrow, vote.milestone);
+            MilestoneStatus::Rejected
+        } else {
+            escrow.vote = Some(vote);
+            MilestoneStatus::Voting
+        };
+        self.milestone_escrows.insert(&event_id, &escrow);
+
+        refund_deposit(init_storage);
+        status
+    }
+
+    /// Rejects a release that wasn't approved before its deadline. Anyone can call it.
+    pub fn finalize_milestone_vote(&mut self, event_id: EventId) {
+        require!(
+            self.internal_watch_detail_event(&event_id).status == Status::Active,
+            "The event is not active"
+        );
+        let mut escrow = self.internal_unwrap_milestone_escrow(&event_id);
+        let vote = escrow
+            .vote
+            .take()
+            .unwrap_or_else(|| env::panic_str("No release is being voted"));
+        require!(
+            env::block_timestamp() > vote.deadline.0,
+            "The vote on this release is not over yet"
+        );
+        self.internal_reject_milestone(&event_id, &mut escrow, vote.milestone);
+        self.milestone_escrows.insert(&event_id, &escrow);
+    }
+
+    /// Sends the owner the released funds whose transfer failed. The finance managers of the
+    /// event can also trigger it.
+    pub fn withdraw_unpaid_milestone_funds(&mut self, event_id: EventId) {
+        require!(
+            self.check_event_role(&event_id, EventRole::Finance),
+            "You are not allowed to withdraw the funds of this event"
+        );
+        let mut escrow = self.internal_unwrap_milestone_escrow(&event_id);
+        let (near, usdt) = (escrow.unpaid_near.0, escrow.unpaid_usdt.0);
+        require!(near > 0 || usdt > 0, "No released funds to withdraw");
+        escrow.unpaid_near = U128(0);
+        escrow.unpaid_usdt = U128(0);
+        self.milestone_escrows.insert(&event_id, &escrow);
+        self.internal_pay_milestone(&event_id, near, usdt);
+    }
+
+    pub fn get_milestone_escrow(&self, event_id: EventId) -> Option<MilestoneEscrow> {
+        self.milestone_escrows.get(&event_id)
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_unwrap_milestone_escrow(&self, event_id: &EventId) -> MilestoneEscrow {
+        self.milestone_escrows
+            .get(event_id)
+            .unwrap_or_else(|| env::panic_str("The event has no milestones"))
+    }
+
+    /// Sponsorships are closed once the first release of an event with milestones has been requested,
+    /// so that the shares keep applying to the amounts the sponsors voted with.
+    pub(crate) fn assert_sponsorship_open(&self, event_id: &EventId) {
+        if let Some(escrow) = self.milestone_escrows.get(event_id) {
+            require!(
+                escrow.next_milestone == 0 && escrow.vote.is_none(),
+                "The event doesn't accept new sponsorships once its milestones have started"
+            );
+        }
+    }
+
+    /// Part of a sponsor's deposit that goes back to them: what has not been released to the
+    /// event owner through milestones, minus the owner's share of a dispute ruling.
+    pub(crate) fn internal_refundable_amount(
+        &self,
+        event_id: &EventId,
+        amount: Balance,
+    ) -> Balance {
+        let amount = match self.milestone_escrows.get(event_id) {
+            Some(escrow) => {
+                amount * (BPS_DENOMINATOR - escrow.released_bps as u128) / BPS_DENOMINATOR
+            }
+            None => amount,
+        };
+        match self.dispute_rulings.get(event_id) {
+            Some(ruling) => {
+                amount * (BPS_DENOMINATOR - ruling.owner_bps() as u128) / BPS_DENOMINATOR
+            }
+            None => amount,
+        }
+    }
+
+    pub(crate) fn internal_release_milestone(
+        &mut self,
+        event_id: &EventId,
+        escrow: &mut MilestoneEscrow,
+        index: u32,
+    ) {
+        let event = self.internal_watch_detail_event(event_id);
+        let milestone = &mut escrow.milestones[index as usize];
+        milestone.status = MilestoneStatus::Released;
+        let released_before = escrow.released_bps as u128;
+        escrow.released_bps += milestone.share_bps;
+        escrow.next_milestone = index + 1;
+
+        // Differences of the cumulative shares, so that the last milestone releases every remaining token
+        let released_after = escrow.released_bps as u128;
+        let tranche = |total: Balance| {
+            total * released_after / BPS_DENOMINATOR - total * released_before / BPS_DENOMINATOR
+        };
+        self.internal_pay_milestone(
+            event_id,
+            tranche(event.total_near),
+            tranche(event.total_usdt),
+        );
+    }
+
+    /// A rejected release cancels the event: sponsors `claim` back what hasn't been released.
+    pub(crate) fn internal_reject_milestone(
+        &mut self,
+        event_id: &EventId,
+        escrow: &mut MilestoneEscrow,
+        index: u32,
+    ) {
+        escrow.milestones[index as usize].status = MilestoneStatus::Rejected;
+        let mut event = self.internal_watch_detail_event(event_id);
+        self.internal_cancel_event(&mut event);
+        self.events.insert(event_id, &event);
+    }
+
+    fn internal_pay_milestone(&mut self, event_id: &EventId, near: Balance, usdt: Balance) {
+        let owner = self.internal_watch_detail_event(event_id).owner;
+        for (token, amount) in [(Token::NEAR, near), (Token::USDT, usdt)] {
+            if amount == 0 {
+                continue;
+            }
+            if let Some(promise) = self.internal_send_token(
+                &token,
+                &owner,
+                amount,
+                Some(format!("Milestone of event {}", event_id)),
+            ) {
+                promise.then(
+                    ext_self::ext(env::current_account_id())
+                        .with_static_gas(FT_TRANSFER_GAS)
+                        .milestone_release_callback(event_id.clone(), token, amount.into()),
+                );
+            }
+        }
+    }
+}
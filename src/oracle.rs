@@ -0,0 +1,234 @@
+use crate::external::ext_self;
+use crate::*;
+use near_sdk::{ext_contract, Promise};
+
+const GAS_FOR_GET_PRICE_DATA: Gas = Gas(10_000_000_000_000);
+const GAS_FOR_PRICE_DATA_CALLBACK: Gas = Gas(10_000_000_000_000);
+/// USD values are returned with 6 decimals
+pub const USD_DECIMALS: u8 = 6;
+/// Prices with more decimals can't be converted, `10^(decimals - USD_DECIMALS)` would overflow
+pub const MAX_PRICE_DECIMALS: u8 = 44;
+
+pub type AssetId = String;
+
+/// Price as returned by the oracle: `amount * multiplier / 10^decimals` of the asset is
+/// worth that many USD.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Price {
+    pub multiplier: U128,
+    pub decimals: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct AssetOptionalPrice {
+    pub asset_id: AssetId,
+    pub price: Option<Price>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceData {
+    /// Timestamp (in nanoseconds) of the prices
+    pub timestamp: U64,
+    pub recency_duration_sec: u32,
+    pub prices: Vec<AssetOptionalPrice>,
+}
+
+#[ext_contract(ext_price_oracle)]
+pub trait PriceOracle {
+    fn get_price_data(&self, asset_ids: Option<Vec<AssetId>>) -> PriceData;
+}
+
+/// Oracle the sponsorships are valued in USD with.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PriceOracleConfig {
+    pub oracle_id: AccountId,
+    /// Assets of the oracle NEAR and USDT are priced as, e.g. `wrap.near`
+    pub near_asset_id: AssetId,
+    pub usdt_asset_id: AssetId,
+    /// How long (in nanoseconds) a price can be used after the oracle published it
+    pub max_staleness: U64,
+}
+
+impl PriceOracleConfig {
+    pub fn asset_id(&self, token: &Token) -> &AssetId {
+        match token {
+            Token::NEAR => &self.near_asset_id,
+            _ => &self.usdt_asset_id,
+        }
+    }
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct CachedPrice {
+    pub multiplier: U128,
+    pub decimals: u8,
+    /// Timestamp (in nanoseconds) the oracle published the price at
+    pub timestamp: U64,
+}
+
+/// Value in USD, with `USD_DECIMALS` decimals.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct UsdValue {
+    pub near: U128,
+    pub usdt: U128,
+    pub total: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the price oracle, or removes it. The cached prices are dropped.
+    pub fn set_price_oracle(&mut self, config: Option<PriceOracleConfig>) {
+        self.assert_owner();
+        self.price_oracle = config;
+        self.token_prices.remove(&Token::NEAR);
+        self.token_prices.remove(&Token::USDT);
+    }
+
+    pub fn get_price_oracle(&self) -> Option<PriceOracleConfig> {
+        self.price_oracle.clone()
+    }
+
+    /// Fetches the prices of NEAR and USDT from the oracle and caches them.
+    pub fn refresh_prices(&mut self) -> Promise {
+        let config = self
+            .price_oracle
+            .clone()
+            .unwrap_or_else(|| env::panic_str("No price oracle is set"));
+        ext_price_oracle::ext(config.oracle_id)
+            .with_static_gas(GAS_FOR_GET_PRICE_DATA)
+            .get_price_data(Some(vec![config.near_asset_id, config.usdt_asset_id]))
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_PRICE_DATA_CALLBACK)
+                    .price_data_callback(),
+            )
+    }
+
+    pub fn get_token_price(&self, token: Token) -> Option<CachedPrice> {
+        self.token_prices.get(&token)
+    }
+
+    /// Returns the totals of the event in USD.
+    pub fn get_event_totals_usd(&self, event_id: EventId) -> UsdValue {
+        let event = self.internal_watch_detail_event(&event_id);
+        self.internal_usd_value(event.total_near, event.total_usdt)
+    }
+
+    /// Returns the funding goals of the event, its hard caps, in USD.
+    pub fn get_event_goals_usd(&self, event_id: EventId) -> Option<UsdValue> {
+        self.internal_watch_detail_event(&event_id);
+        let limits = self.sponsorship_limits.get(&event_id)?;
+        if limits.near.hard_cap.is_none() && limits.usdt.hard_cap.is_none() {
+            return None;
+        }
+        Some(self.internal_usd_value(
+            limits.near.hard_cap.map_or(0, |cap| cap.0),
+            limits.usdt.hard_cap.map_or(0, |cap| cap.0),
+        ))
+    }
+
+    /// Returns the minimum contribution of each tier of the event in USD.
+    pub fn get_sponsor_tiers_usd(&self, event_id: EventId) -> Vec<(String, U128)> {
+        let event_tiers = match self.event_tiers.get(&event_id) {
+            Some(event_tiers) => event_tiers,
+            None => return vec![],
+        };
+        event_tiers
+            .tiers
+            .into_iter()
+            .map(|tier| {
+                let usd =
+                    self.internal_to_usd(&event_tiers.reference_token, tier.min_contribution.0);
+                (tier.name, usd.into())
+            })
+            .collect()
+    }
+}
+
+impl Contract {
+    /// Caches the prices of `price_data` that are fresh and newer than the cached ones.
+    pub(crate) fn internal_cache_prices(&mut self, price_data: PriceData) {
+        let config = match &self.price_oracle {
+            Some(config) => config.clone(),
+            None => return,
+        };
+        let fresh_until = price_data
+            .timestamp
+            .0
+            .saturating_add(config.max_staleness.0);
+        if env::block_timestamp() > fresh_until {
+            return;
+        }
+        for token in [Token::NEAR, Token::USDT] {
+            let price = price_data
+                .prices
+                .iter()
+                .find(|asset| asset.asset_id == *config.asset_id(&token))
+                .and_then(|asset| asset.price.clone());
+            let price = match price {
+                Some(price) if price.multiplier.0 > 0 && price.decimals <= MAX_PRICE_DECIMALS => {
+                    price
+                }
+                _ => continue,
+            };
+            let cached_at = self
+                .token_prices
+                .get(&token)
+                .map_or(0, |cached| cached.timestamp.0);
+            if price_data.timestamp.0 > cached_at {
+                self.token_prices.insert(
+                    &token,
+                    &CachedPrice {
+                        multiplier: price.multiplier,
+                        decimals: price.decimals,
+                        timestamp: price_data.timestamp,
+                    },
+                );
+            }
+        }
+    }
+
+    /// Value of `amount` of `token` in USD, with `USD_DECIMALS` decimals. The cached price of
+    /// the token must not be stale.
+    pub(crate) fn internal_to_usd(&self, token: &Token, amount: Balance) -> Balance {
+        if amount == 0 {
+            return 0;
+        }
+        let config = self
+            .price_oracle
+            .as_ref()
+            .unwrap_or_else(|| env::panic_str("No price oracle is set"));
+        let price = self
+            .token_prices
+            .get(token)
+            .unwrap_or_else(|| env::panic_str("No price cached, call refresh_prices"));
+        require!(
+            env::block_timestamp() <= price.timestamp.0.saturating_add(config.max_staleness.0),
+            "The cached price is stale, call refresh_prices"
+        );
+        let value = amount
+            .checked_mul(price.multiplier.0)
+            .unwrap_or_else(|| env::panic_str("The amount is too large to be valued"));
+        if price.decimals >= USD_DECIMALS {
+            value / 10u128.pow((price.decimals - USD_DECIMALS) as u32)
+        } else {
+            value * 10u128.pow((USD_DECIMALS - price.decimals) as u32)
+        }
+    }
+
+    fn internal_usd_value(&self, near: Balance, usdt: Balance) -> UsdValue {
+        let near = self.internal_to_usd(&Token::NEAR, near);
+        let usdt = self.internal_to_usd(&Token::USDT, usdt);
+        UsdValue {
+            near: near.into(),
+            usdt: usdt.into(),
+            total: (near + usdt).into(),
+        }
+    }
+}
@@ -0,0 +1,168 @@
+use crate::*;
+
+/// Engagement points are kept apart from `accounts` and can't be transferred between accounts:
+/// operators award them, and their holder can only redeem them into PLAT from the reward pool.
+#[near_bindgen]
+impl Contract {
+    pub fn add_point_operator(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        self.point_operators.insert(&account_id);
+    }
+
+    pub fn remove_point_operator(&mut self, account_id: AccountId) {
+        self.assert_owner();
+        self.point_operators.remove(&account_id);
+    }
+
+    /// Awards `amount` points of the current season to `account_id`.
+    pub fn award_points(&mut self, account_id: AccountId, amount: U128, reason: Option<String>) {
+        require!(
+            self.point_operators
+                .contains(&env::predecessor_account_id()),
+            "Only a point operator can award points"
+        );
+        require!(amount.0 > 0, "The amount should be a positive number");
+
+        let points = self.internal_points_of(&account_id);
+        let new_points = points
+            .checked_add(amount.0)
+            .unwrap_or_else(|| env::panic_str("Points overflow"));
+        self.internal_set_points(&account_id, points, new_points);
+
+        PointsAward {
+            account_id: &account_id,
+            amount: &amount,
+            season: self.points_season,
+            reason: reason.as_deref(),
+        }
+        .emit();
+    }
+
+    /// Starts a new season. Every account starts it with no points; the points of the
+    /// previous seasons stay readable through `get_points`.
+    pub fn start_points_season(&mut self) -> u32 {
+        self.assert_owner();
+        self.points_season += 1;
+        self.points_ranking = TreeMap::new(
+            Prefix::PointsRanking {
+                season: self.points_season,
+            }
+            .try_to_vec()
+            .unwrap(),
+        );
+        PointsSeasonStart {
+            season: self.points_season,
+        }
+        .emit();
+        self.points_season
+    }
+
+    /// Sets how much PLAT one point is redeemed for.
+    pub fn set_points_rate(&mut self, rate: U128) {
+        self.assert_owner();
+        self.points_rate = rate.0;
+    }
+
+    /// Moves `amount` PLAT from the owner's balance to the pool points are redeemed from.
+    pub fn fund_points_pool(&mut self, amount: U128) {
+        self.assert_owner();
+        let owner_id = self.owner_id.clone();
+        self.internal_lock(&owner_id, amount.0, Some("Points reward pool".to_string()));
+        self.points_pool += amount.0;
+    }
+
+    /// Converts `points` of the caller's current season points into PLAT at the current rate.
+    /// Returns the amount of PLAT received.
+    pub fn redeem_points(&mut self, points: U128) -> U128 {
+        let account_id = env::predecessor_account_id();
+        require!(points.0 > 0, "The amount should be a positive number");
+        require!(self.points_rate > 0, "Points can't be redeemed yet");
+
+        let balance = self.internal_points_of(&account_id);
+        let new_balance = balance
+            .checked_sub(points.0)
+            .unwrap_or_else(|| env::panic_str("The account doesn't have enough points"));
+        let amount = points
+            .0
+            .checked_mul(self.points_rate)
+            .unwrap_or_else(|| env::panic_str("Balance overflow"));
+        require!(
+            amount <= self.points_pool,
+            "The reward pool doesn't have enough tokens"
+        );
+
+        self.internal_set_points(&account_id, balance, new_balance);
+        self.points_pool -= amount;
+        self.internal_unlock(&account_id, amount, Some("Points redeemed".to_string()));
+
+        PointsRedeem {
+            account_id: &account_id,
+            points: &points,
+            amount: &U128(amount),
+            season: self.points_season,
+        }
+        .emit();
+        amount.into()
+    }
+
+    /// Returns the points of an account in the given season, the current one by default.
+    pub fn get_points(&self, account_id: AccountId, season: Option<u32>) -> U128 {
+        let season = season.unwrap_or(self.points_season);
+        self.points.get(&(season, account_id)).unwrap_or(0).into()
+    }
+
+    pub fn get_points_season(&self) -> u32 {
+        self.points_season
+    }
+
+    /// Returns the accounts with the most points in the current season, highest first.
+    pub fn get_points_leaderboard(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(AccountId, U128)> {
+        self.points_ranking
+            .iter_rev()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|((points, account_id), _)| (account_id, U128(points)))
+            .collect()
+    }
+
+    pub fn get_points_rate(&self) -> U128 {
+        self.points_rate.into()
+    }
+
+    pub fn get_points_pool(&self) -> U128 {
+        self.points_pool.into()
+    }
+
+    pub fn get_point_operators(&self) -> Vec<AccountId> {
+        self.point_operators.to_vec()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_points_of(&self, account_id: &AccountId) -> Balance {
+        self.points
+            .get(&(self.points_season, account_id.clone()))
+            .unwrap_or(0)
+    }
+
+    /// Updates the current season points of an account along with its place in the ranking.
+    pub(crate) fn internal_set_points(
+        &mut self,
+        account_id: &AccountId,
+        old: Balance,
+        new: Balance,
+    ) {
+        if old > 0 {
+            self.points_ranking.remove(&(old, account_id.clone()));
+        }
+        if new > 0 {
+            self.points_ranking.insert(&(new, account_id.clone()), &());
+        }
+        self.points
+            .insert(&(self.points_season, account_id.clone()), &new);
+    }
+}
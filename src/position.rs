@@ -0,0 +1,172 @@
+use crate::*;
+use std::collections::HashMap;
+
+#[near_bindgen]
+impl Contract {
+    /// Hands the caller's sponsorship of the event, with its refund rights and perks, to
+    /// `receiver_id`, who must be registered with the contract. If the receiver already
+    /// sponsors the event, the move is only allowed in `merge` mode, which adds the caller's
+    /// sponsorship to theirs. Sponsorships of an event can only move while it is active and no
+    /// vote is open on it, so that a stake can't vote twice.
+    /// The attached deposit covers the storage of the receiver's sponsorship, the rest is refunded.
+    #[payable]
+    pub fn transfer_sponsorship(
+        &mut self,
+        event_id: EventId,
+        receiver_id: AccountId,
+        merge: Option<bool>,
+    ) {
+        let init_storage = env::storage_usage();
+        assert_at_least_one_yocto();
+        let sender_id = env::predecessor_account_id();
+        let mut event = self.internal_watch_detail_event(&event_id);
+        require!(event.status == Status::Active, "The event is not active");
+        require!(
+            !self.internal_has_open_vote(&event_id),
+            "Sponsorships can't be transferred while a vote is open on the event"
+        );
+        require!(
+            sender_id != receiver_id,
+            "Sender and receiver should be different"
+        );
+        require!(
+            self.accounts.contains_key(&receiver_id),
+            "The receiver is not registered"
+        );
+        self.assert_sponsor_allowed(&event_id, &receiver_id);
+        let amount = self
+            .internal_unwrap_balance(&sender_id, &event_id)
+            .unwrap_or_else(|_| env::panic_str("You haven't sponse this event before"));
+        let merged = self
+            .internal_unwrap_balance(&receiver_id, &event_id)
+            .is_ok();
+        require!(
+            !merged || merge.unwrap_or(false),
+            "The receiver already sponsors this event"
+        );
+        let sender_tier = self.internal_tier_index(&sender_id, &event_id);
+        let old_tier = self.internal_tier_index(&receiver_id, &event_id);
+        let old_amount = self
+            .internal_unwrap_balance(&receiver_id, &event_id)
+            .unwrap_or(Amount {
+                token_near: 0,
+                token_usdt: 0,
+            });
+
+        // The sender leaves the event and the receiver takes their place
+        self.handle_sponser_claim(sender_id.clone(), event_id.clone());
+        event.sponsers.retain(|item| *item != sender_id);
+        if !merged {
+            event.sponsers.push(receiver_id.clone());
+        }
+        self.events.insert(&event_id, &event);
+
+        let mut sponse = self.sponser_to_sponse.get(&receiver_id).unwrap_or(Sponse {
+            events: HashSet::new(),
+            map_event_amount: HashMap::new(),
+        });
+        let receiver_amount = sponse
+            .map_event_amount
+            .entry(event_id.clone())
+            .or_insert(Amount {
+                token_near: 0,
+                token_usdt: 0,
+            });
+        receiver_amount.token_near += amount.token_near;
+        receiver_amount.token_usdt += amount.token_usdt;
+        self.assert_within_max_contribution(&event_id, receiver_amount);
+        sponse.events.insert(event_id.clone());
+        self.sponser_to_sponse.insert(&receiver_id, &sponse);
+        self.internal_update_leaderboards(&receiver_id, &event_id, &old_amount);
+
+        self.internal_move_sponsorship_records(&event_id, &sender_id, &receiver_id);
+        self.internal_track_sponsor(&receiver_id);
+        let receiver_kind = if merged {
+            ContributionKind::TopUp
+        } else {
+            ContributionKind::Initial
+        };
+        for (token, amount) in [
+            (Token::NEAR, amount.token_near),
+            (Token::USDT, amount.token_usdt),
+        ] {
+            self.internal_record_contribution(
+                &sender_id,
+                &event_id,
+                &token,
+                amount,
+                ContributionKind::Withdrawal,
+                Some(format!("Transferred to {}", receiver_id)),
+            );
+            self.internal_record_contribution(
+                &receiver_id,
+                &event_id,
+                &token,
+                amount,
+                receiver_kind.clone(),
+                Some(format!("Transferred from {}", sender_id)),
+            );
+        }
+        self.internal_burn_receipt(&sender_id, &event_id, "Sponsorship transferred");
+        self.internal_mint_receipt(&receiver_id, &event_id);
+        self.internal_check_tier_change(&sender_id, &event_id, sender_tier);
+        self.internal_check_tier_change(&receiver_id, &event_id, old_tier);
+
+        SponsorshipTransfer {
+            event_id: &event_id,
+            old_sponser_id: &sender_id,
+            new_sponser_id: &receiver_id,
+            near: &U128(amount.token_near),
+            usdt: &U128(amount.token_usdt),
+            merged,
+        }
+        .emit();
+
+        refund_deposit(init_storage);
+    }
+}
+
+impl Contract {
+    /// Moves what is kept for each sponsorship of the event from the sender to the receiver.
+    fn internal_move_sponsorship_records(
+        &mut self,
+        event_id: &EventId,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+    ) {
+        let sender_key = (event_id.clone(), sender_id.clone());
+        let receiver_key = (event_id.clone(), receiver_id.clone());
+
+        // The withdrawal window keeps running from the earliest deposit
+        if let Some(sponsored_at) = self.sponsored_at.remove(&sender_key) {
+            let sponsored_at = self
+                .sponsored_at
+                .get(&receiver_key)
+                .map_or(sponsored_at, |at| at.min(sponsored_at));
+            self.sponsored_at.insert(&receiver_key, &sponsored_at);
+        }
+
+        // Fees are refunded with the sponsorship they were taken on
+        if let Some(fees) = self.sponser_fees.remove(&sender_key) {
+            let mut receiver_fees = self.sponser_fees.get(&receiver_key).unwrap_or(Amount {
+                token_near: 0,
+                token_usdt: 0,
+            });
+            receiver_fees.token_near += fees.token_near;
+            receiver_fees.token_usdt += fees.token_usdt;
+            self.sponser_fees.insert(&receiver_key, &receiver_fees);
+        }
+    }
+
+    /// Whether the sponsors of the event are voting on its cancellation or on a milestone.
+    fn internal_has_open_vote(&self, event_id: &EventId) -> bool {
+        let cancellation_open = self.cancellation_votes.get(event_id).map_or(false, |vote| {
+            !vote.passed && env::block_timestamp() <= vote.deadline.0
+        });
+        let milestone_open = self
+            .milestone_escrows
+            .get(event_id)
+            .map_or(false, |escrow| escrow.vote.is_some());
+        cancellation_open || milestone_open
+    }
+}
@@ -0,0 +1,245 @@
+use crate::external::ext_self;
+use crate::*;
+
+/// Maximum number of payouts sent by a single `pay_prizes` call.
+pub const MAX_PRIZE_BATCH: u64 = 10;
+/// Denominator of basis point shares.
+pub const BPS_DENOMINATOR: u128 = 10_000;
+
+/// How much of the event funds a winner receives.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PrizeShare {
+    /// A fixed amount of the token
+    Fixed { amount: U128 },
+    /// A share in basis points of the event total in the token
+    Bps { bps: u16 },
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PrizeWinner {
+    pub account_id: AccountId,
+    /// NEAR or USDT, the tokens sponsors deposit into events
+    pub token: Token,
+    pub share: PrizeShare,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PayoutStatus {
+    /// Not sent yet
+    Pending,
+    /// Sent, waiting for the result of the transfer
+    InFlight,
+    Paid,
+    /// The transfer failed, the payout can be retried
+    Failed,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PrizePayout {
+    pub account_id: AccountId,
+    pub token: Token,
+    pub amount: U128,
+    pub status: PayoutStatus,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct PrizeDistribution {
+    /// Number of payouts of the distribution
+    pub payouts: u64,
+    /// Index of the first payout that hasn't been sent yet
+    pub next_index: u64,
+    pub total_near: U128,
+    pub total_usdt: U128,
+    /// Amounts confirmed as received by the winners
+    pub paid_near: U128,
+    pub paid_usdt: U128,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Submits the winners of a finished event. Amounts are computed right away from the event totals
+    /// and their sum in each token must not exceed what the sponsors deposited and is still held by
+    /// the contract; it is taken out of the event totals so that it can't be paid twice.
+    /// The payouts are then sent with `pay_prizes`. The attached deposit covers the storage of the
+    /// payouts, the rest is refunded.
+    #[payable]
+    pub fn submit_prize_winners(
+        &mut self,
+        event_id: EventId,
+        winners: Vec<PrizeWinner>,
+    ) -> PrizeDistribution {
+        let init_storage = env::storage_usage();
+        let mut event = self.internal_watch_detail_event(&event_id);
+        require!(
+            self.check_event_role(&event_id, EventRole::Finance),
+            "You are not allowed to submit the winners of this event"
+        );
+        require!(
+            event.status == Status::Finish,
+            "The event has not finished yet"
+        );
+        require!(
+            !self.prize_distributions.contains_key(&event_id),
+            "The winners of this event have already been submitted"
+        );
+        require!(!winners.is_empty(), "No winners to pay");
+        let payouts = winners.len() as u64;
+
+        // Funds already released to the owner can't be paid out again
+        let available_near = self.internal_refundable_amount(&event_id, event.total_near);
+        let available_usdt = self.internal_refundable_amount(&event_id, event.total_usdt);
+        let mut total_near: Balance = 0;
+        let mut total_usdt: Balance = 0;
+        for (index, winner) in winners.into_iter().enumerate() {
+            let event_total = match winner.token {
+                Token::NEAR => available_near,
+                Token::USDT => available_usdt,
+                Token::PLAT => env::panic_str("Prizes are paid in NEAR or USDT"),
+            };
+            let amount = match winner.share {
+                PrizeShare::Fixed { amount } => amount.0,
+                PrizeShare::Bps { bps } => event_total * bps as u128 / BPS_DENOMINATOR,
+            };
+            require!(amount > 0, "Every prize should be a positive amount");
+            let total = match winner.token {
+                Token::NEAR => &mut total_near,
+                _ => &mut total_usdt,
+            };
+            *total = total
+                .checked_add(amount)
+                .unwrap_or_else(|| env::panic_str("Balance overflow"));
+
+            self.prize_payouts.insert(
+                &(event_id.clone(), index as u64),
+                &PrizePayout {
+                    account_id: winner.account_id,
+                    token: winner.token,
+                    amount: amount.into(),
+                    status: PayoutStatus::Pending,
+                },
+            );
+        }
+        require!(
+            total_near <= available_near,
+            "The NEAR prizes exceed the NEAR sponsored to the event"
+        );
+        require!(
+            total_usdt <= available_usdt,
+            "The USDT prizes exceed the USDT sponsored to the event"
+        );
+        event.total_near -= total_near;
+        event.total_usdt -= total_usdt;
+        self.events.insert(&event_id, &event);
+        self.internal_on_total_change(&event, &Token::NEAR, event.total_near + total_near);
+        self.internal_on_total_change(&event, &Token::USDT, event.total_usdt + total_usdt);
+
+        let distribution = PrizeDistribution {
+            payouts,
+            next_index: 0,
+            total_near: total_near.into(),
+            total_usdt: total_usdt.into(),
+            paid_near: U128(0),
+            paid_usdt: U128(0),
+        };
+        self.prize_distributions.insert(&event_id, &distribution);
+
+        refund_deposit(init_storage);
+        distribution
+    }
+
+    /// Sends the next batch of at most `limit` pending payouts, each followed by a callback
+    /// recording whether it went through. Anyone can push the distribution forward.
+    pub fn pay_prizes(&mut self, event_id: EventId, limit: Option<u64>) -> u64 {
+        let mut distribution = self.internal_unwrap_prize_distribution(&event_id);
+        let limit = std::cmp::min(limit.unwrap_or(MAX_PRIZE_BATCH), MAX_PRIZE_BATCH);
+        let to_index = std::cmp::min(distribution.next_index + limit, distribution.payouts);
+        require!(
+            distribution.next_index < to_index,
+            "Every prize has already been sent"
+        );
+
+        for index in distribution.next_index..to_index {
+            self.internal_send_prize(&event_id, index);
+        }
+        let sent = to_index - distribution.next_index;
+        distribution.next_index = to_index;
+        self.prize_distributions.insert(&event_id, &distribution);
+        sent
+    }
+
+    /// Sends a payout whose transfer failed again.
+    pub fn retry_prize_payout(&mut self, event_id: EventId, index: u64) {
+        let payout = self.internal_unwrap_prize_payout(&event_id, index);
+        require!(
+            payout.status == PayoutStatus::Failed,
+            "Only a failed payout can be retried"
+        );
+        self.internal_send_prize(&event_id, index);
+    }
+
+    pub fn get_prize_distribution(&self, event_id: EventId) -> Option<PrizeDistribution> {
+        self.prize_distributions.get(&event_id)
+    }
+
+    pub fn get_prize_payouts(
+        &self,
+        event_id: EventId,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<PrizePayout> {
+        let distribution = self.internal_unwrap_prize_distribution(&event_id);
+        let from_index = from_index.unwrap_or(0);
+        let to_index = std::cmp::min(from_index + limit.unwrap_or(50), distribution.payouts);
+        (from_index..to_index)
+            .map(|index| self.internal_unwrap_prize_payout(&event_id, index))
+            .collect()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_unwrap_prize_distribution(
+        &self,
+        event_id: &EventId,
+    ) -> PrizeDistribution {
+        self.prize_distributions
+            .get(event_id)
+            .unwrap_or_else(|| env::panic_str("The winners of this event have not been submitted"))
+    }
+
+    pub(crate) fn internal_unwrap_prize_payout(
+        &self,
+        event_id: &EventId,
+        index: u64,
+    ) -> PrizePayout {
+        self.prize_payouts
+            .get(&(event_id.clone(), index))
+            .unwrap_or_else(|| env::panic_str("Prize payout not found"))
+    }
+
+    /// Transfers a payout and marks it in flight until `prize_payout_callback` records the result.
+    pub(crate) fn internal_send_prize(&mut self, event_id: &EventId, index: u64) {
+        let mut payout = self.internal_unwrap_prize_payout(event_id, index);
+        payout.status = PayoutStatus::InFlight;
+        self.prize_payouts
+            .insert(&(event_id.clone(), index), &payout);
+
+        let promise = self
+            .internal_send_token(
+                &payout.token,
+                &payout.account_id,
+                payout.amount.0,
+                Some(format!("Prize of event {}", event_id)),
+            )
+            .unwrap_or_else(|| env::panic_str("Prizes are paid in NEAR or USDT"));
+        promise.then(
+            ext_self::ext(env::current_account_id())
+                .with_static_gas(FT_TRANSFER_GAS)
+                .prize_payout_callback(event_id.clone(), index),
+        );
+    }
+}
@@ -0,0 +1,248 @@
+use crate::*;
+
+pub type QuestId = u64;
+
+/// A step of a quest. Off-chain actions (follow, join, ...) are tasks confirmed by their client
+/// or the backend; the other steps are checked by the contract itself.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum QuestStep {
+    /// Having been rewarded for the task
+    Task { task_id: TaskId },
+    /// Holding at least `amount` PLAT, when the step is verified and when the bonus is claimed
+    HoldPlat { amount: U128 },
+    /// Sponsoring the event, when the step is verified and when the bonus is claimed
+    SponsorEvent { event_id: EventId },
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum QuestStatus {
+    Active,
+    Closed,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Quest {
+    pub id: QuestId,
+    pub creator: AccountId,
+    pub name: String,
+    pub steps: Vec<QuestStep>,
+    /// Whether the steps have to be verified in the given order
+    pub ordered: bool,
+    /// PLAT paid to each account that completes every step
+    pub bonus: U128,
+    /// Remaining PLAT for bonuses
+    pub budget: U128,
+    pub max_completions: u64,
+    pub completions: u64,
+    pub status: QuestStatus,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct QuestProgress {
+    /// Whether each step of the quest has been verified
+    pub completed_steps: Vec<bool>,
+    pub rewarded: bool,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Creates a quest and locks `bonus * max_completions` PLAT from the caller's balance to pay the bonuses.
+    /// The attached deposit covers the storage of the quest, the rest is refunded.
+    #[payable]
+    pub fn create_quest(
+        &mut self,
+        name: String,
+        steps: Vec<QuestStep>,
+        ordered: bool,
+        bonus: U128,
+        max_completions: u64,
+    ) -> QuestId {
+        let init_storage = env::storage_usage();
+        let creator = env::predecessor_account_id();
+        require!(!steps.is_empty(), "A quest should have at least one step");
+        require!(bonus.0 > 0, "The bonus should be a positive number");
+        require!(
+            max_completions > 0,
+            "The quest should be completable at least once"
+        );
+        let budget = bonus
+            .0
+            .checked_mul(max_completions as u128)
+            .unwrap_or_else(|| env::panic_str("Balance overflow"));
+
+        let id = self.next_quest_id;
+        self.next_quest_id += 1;
+        let quest = Quest {
+            id,
+            creator: creator.clone(),
+            name,
+            steps,
+            ordered,
+            bonus,
+            budget: U128(budget),
+            max_completions,
+            completions: 0,
+            status: QuestStatus::Active,
+        };
+        self.quests.insert(&id, &quest);
+        self.internal_lock(&creator, budget, Some(format!("Budget of quest {}", id)));
+
+        refund_deposit(init_storage);
+        id
+    }
+
+    /// Checks step `step_index` of the quest for the caller and records it as done.
+    /// The attached deposit covers the storage of the caller's progress, the rest is refunded.
+    #[payable]
+    pub fn verify_quest_step(&mut self, quest_id: QuestId, step_index: u32) -> QuestProgress {
+        let init_storage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+        let quest = self.internal_unwrap_quest(quest_id);
+        require!(quest.status == QuestStatus::Active, "The quest is closed");
+        let step = quest
+            .steps
+            .get(step_index as usize)
+            .unwrap_or_else(|| env::panic_str("Invalid quest step"));
+
+        let key = (quest_id, account_id.clone());
+        let mut progress = self.quest_progress.get(&key).unwrap_or(QuestProgress {
+            completed_steps: vec![false; quest.steps.len()],
+            rewarded: false,
+        });
+        require!(
+            !progress.completed_steps[step_index as usize],
+            "This step is already verified"
+        );
+        if quest.ordered {
+            require!(
+                progress.completed_steps[..step_index as usize]
+                    .iter()
+                    .all(|done| *done),
+                "The previous steps of the quest should be verified first"
+            );
+        }
+        require!(
+            self.internal_check_quest_step(&account_id, step),
+            "The quest step is not completed"
+        );
+
+        progress.completed_steps[step_index as usize] = true;
+        self.quest_progress.insert(&key, &progress);
+
+        refund_deposit(init_storage);
+        progress
+    }
+
+    /// Pays the quest bonus to the caller once every step has been verified. The caller must
+    /// still hold the PLAT and the sponsorships the quest asks for.
+    pub fn claim_quest_bonus(&mut self, quest_id: QuestId) -> U128 {
+        let account_id = env::predecessor_account_id();
+        let mut quest = self.internal_unwrap_quest(quest_id);
+        require!(quest.status == QuestStatus::Active, "The quest is closed");
+        require!(
+            quest.completions < quest.max_completions,
+            "The quest has reached its maximum number of completions"
+        );
+
+        let key = (quest_id, account_id.clone());
+        let mut progress = self
+            .quest_progress
+            .get(&key)
+            .unwrap_or_else(|| env::panic_str("You haven't started this quest yet"));
+        require!(!progress.rewarded, "The bonus has already been claimed");
+        require!(
+            progress.completed_steps.iter().all(|done| *done),
+            "Every step of the quest should be verified first"
+        );
+        require!(
+            quest
+                .steps
+                .iter()
+                .filter(|step| !matches!(step, QuestStep::Task { .. }))
+                .all(|step| self.internal_check_quest_step(&account_id, step)),
+            "The account no longer holds the PLAT or the sponsorships the quest asks for"
+        );
+
+        progress.rewarded = true;
+        self.quest_progress.insert(&key, &progress);
+        quest.budget = U128(quest.budget.0 - quest.bonus.0);
+        quest.completions += 1;
+        self.quests.insert(&quest_id, &quest);
+
+        self.internal_unlock(
+            &account_id,
+            quest.bonus.0,
+            Some(format!("Bonus of quest {}", quest_id)),
+        );
+        quest.bonus
+    }
+
+    /// Closes the quest and returns the remaining budget to its creator.
+    pub fn close_quest(&mut self, quest_id: QuestId) -> U128 {
+        let mut quest = self.internal_unwrap_quest(quest_id);
+        require!(
+            env::predecessor_account_id() == quest.creator,
+            "Only the creator can close the quest"
+        );
+        require!(
+            quest.status == QuestStatus::Active,
+            "The quest is already closed"
+        );
+
+        let refund = quest.budget;
+        quest.budget = U128(0);
+        quest.status = QuestStatus::Closed;
+        self.quests.insert(&quest_id, &quest);
+
+        if refund.0 > 0 {
+            self.internal_unlock(
+                &quest.creator,
+                refund.0,
+                Some(format!("Refund of quest {}", quest_id)),
+            );
+        }
+        refund
+    }
+
+    pub fn get_quest(&self, quest_id: QuestId) -> Option<Quest> {
+        self.quests.get(&quest_id)
+    }
+
+    pub fn get_quest_progress(
+        &self,
+        quest_id: QuestId,
+        account_id: AccountId,
+    ) -> Option<QuestProgress> {
+        self.quest_progress.get(&(quest_id, account_id))
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_unwrap_quest(&self, quest_id: QuestId) -> Quest {
+        self.quests
+            .get(&quest_id)
+            .unwrap_or_else(|| env::panic_str("Quest not found"))
+    }
+
+    pub(crate) fn internal_check_quest_step(
+        &self,
+        account_id: &AccountId,
+        step: &QuestStep,
+    ) -> bool {
+        match step {
+            QuestStep::Task { task_id } => self
+                .task_participants
+                .contains(&(task_id.clone(), account_id.clone())),
+            QuestStep::HoldPlat { amount } => {
+                self.accounts.get(account_id).unwrap_or(0) >= amount.0
+            }
+            QuestStep::SponsorEvent { event_id } => {
+                self.internal_unwrap_balance(account_id, event_id).is_ok()
+            }
+        }
+    }
+}
@@ -0,0 +1,419 @@
+use near_sdk::json_types::{Base58CryptoHash, Base64VecU8};
+use near_sdk::CryptoHash;
+
+use crate::*;
+
+pub type RaffleId = u64;
+
+/// Most winners a raffle can have, so that paying them and the callbacks of the payouts fits
+/// in the gas of one call.
+pub const MAX_RAFFLE_WINNERS: u32 = 10;
+/// Time (in nanoseconds) after the creation of a raffle from which anyone can cancel it
+/// if it still hasn't been drawn.
+pub const RAFFLE_DRAW_TIMEOUT: u64 = 30 * 24 * 60 * 60 * 1_000_000_000;
+
+/// Who can register for a raffle.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum RaffleEligibility {
+    /// Accounts rewarded for the task
+    Task { task_id: TaskId },
+    /// Sponsors of the event
+    Event { event_id: EventId },
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum RaffleStatus {
+    Registration,
+    Closed,
+    Drawn,
+    Cancelled,
+}
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct Raffle {
+    pub id: RaffleId,
+    pub operator: AccountId,
+    pub name: String,
+    pub eligibility: RaffleEligibility,
+    /// Token the prizes are paid in
+    pub token: Token,
+    /// Prize of each winner
+    pub prize: U128,
+    pub winners_count: u32,
+    /// Amount of the prize pool deposited so far, and the account that deposited it
+    pub funded: U128,
+    pub funder: Option<AccountId>,
+    pub participants: u64,
+    /// sha256 of the secret the operator reveals when drawing
+    pub commitment: Base58CryptoHash,
+    pub status: RaffleStatus,
+    pub seed: Option<Base58CryptoHash>,
+    pub winners: Vec<AccountId>,
+    /// Timestamp (in nanoseconds) of the creation of the raffle
+    pub created_at: U64,
+    /// Payouts whose transfer failed, to be sent again with `retry_raffle_payout`
+    pub unpaid: Vec<(AccountId, U128)>,
+}
+
+impl Raffle {
+    pub fn prize_pool(&self) -> Balance {
+        self.prize.0 * self.winners_count as u128
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Creates a raffle operated by the caller. `commitment` is the sha256 of a secret the operator
+    /// reveals in `draw_raffle`, so that the draw doesn't depend on the block seed alone.
+    /// A PLAT prize pool is locked from the caller's balance right away; NEAR is deposited with
+    /// `fund_raffle_native` and USDT by `ft_transfer_call` with the message `{"raffle_id": <id>}`.
+    /// The attached deposit covers the storage of the raffle, the rest is refunded.
+    #[payable]
+    pub fn create_raffle(
+        &mut self,
+        name: String,
+        eligibility: RaffleEligibility,
+        token: Token,
+        prize: U128,
+        winners_count: u32,
+        commitment: Base58CryptoHash,
+    ) -> RaffleId {
+        let init_storage = env::storage_usage();
+        let operator = env::predecessor_account_id();
+        require!(prize.0 > 0, "The prize should be a positive number");
+        require!(
+            winners_count > 0,
+            "The raffle should have at least one winner"
+        );
+        require!(
+            winners_count <= MAX_RAFFLE_WINNERS,
+            format!(
+                "A raffle can't have more than {} winners",
+                MAX_RAFFLE_WINNERS
+            )
+        );
+
+        let id = self.next_raffle_id;
+        self.next_raffle_id += 1;
+        let mut raffle = Raffle {
+            id,
+            operator: operator.clone(),
+            name,
+            eligibility,
+            token,
+            prize,
+            winners_count,
+            funded: U128(0),
+            funder: None,
+            participants: 0,
+            commitment,
+            status: RaffleStatus::Registration,
+            seed: None,
+            winners: vec![],
+            created_at: U64(env::block_timestamp()),
+            unpaid: vec![],
+        };
+        let prize_pool = prize
+            .0
+            .checked_mul(winners_count as u128)
+            .unwrap_or_else(|| env::panic_str("Balance overflow"));
+        if raffle.token == Token::PLAT {
+            self.internal_lock(
+                &operator,
+                prize_pool,
+                Some(format!("Prizes of raffle {}", id)),
+            );
+            raffle.funded = U128(prize_pool);
+            raffle.funder = Some(operator);
+        }
+        self.raffles.insert(&id, &raffle);
+
+        refund_deposit(init_storage);
+        id
+    }
+
+    /// Deposits the NEAR prize pool of a raffle. The attached deposit must be the missing amount.
+    #[payable]
+    pub fn fund_raffle_native(&mut self, raffle_id: RaffleId) {
+        self.internal_fund_raffle(
+            &env::predecessor_account_id(),
+            raffle_id,
+            Token::NEAR,
+            env::attached_deposit(),
+        );
+    }
+
+    /// Registers the caller for the raffle if they are eligible.
+    /// The attached deposit covers the storage of the registration, the rest is refunded.
+    #[payable]
+    pub fn join_raffle(&mut self, raffle_id: RaffleId) {
+        let init_storage = env::storage_usage();
+        let account_id = env::predecessor_account_id();
+        let mut raffle = self.internal_unwrap_raffle(raffle_id);
+        require!(
+            raffle.status == RaffleStatus::Registration,
+            "The registration of the raffle is closed"
+        );
+        require!(
+            self.internal_is_raffle_eligible(&raffle.eligibility, &account_id),
+            "You are not eligible for this raffle"
+        );
+        require!(
+            self.raffle_entries.insert(&(raffle_id, account_id.clone())),
+            "You have already joined this raffle"
+        );
+
+        self.raffle_participants
+            .insert(&(raffle_id, raffle.participants), &account_id);
+        raffle.participants += 1;
+        self.raffles.insert(&raffle_id, &raffle);
+
+        refund_deposit(init_storage);
+    }
+
+    pub fn close_raffle_registration(&mut self, raffle_id: RaffleId) {
+        let mut raffle = self.internal_unwrap_raffle(raffle_id);
+        require!(
+            env::predecessor_account_id() == raffle.operator,
+            "Only the operator can close the registration"
+        );
+        require!(
+            raffle.status == RaffleStatus::Registration,
+            "The registration of the raffle is already closed"
+        );
+        raffle.status = RaffleStatus::Closed;
+        self.raffles.insert(&raffle_id, &raffle);
+    }
+
+    /// Reveals the operator's secret and draws the winners from `sha256(random_seed || secret)`.
+    /// Participants that are no longer eligible, like sponsors whose sponsorship is gone, can't win.
+    /// Each winner is paid the prize; prizes left over when there are fewer eligible participants
+    /// than winners go back to the operator.
+    pub fn draw_raffle(&mut self, raffle_id: RaffleId, secret: Base64VecU8) -> Vec<AccountId> {
+        let mut raffle = self.internal_unwrap_raffle(raffle_id);
+        require!(
+            env::predecessor_account_id() == raffle.operator,
+            "Only the operator can draw the raffle"
+        );
+        require!(
+            raffle.status == RaffleStatus::Closed,
+            "The registration of the raffle should be closed first"
+        );
+        require!(
+            raffle.funded.0 == raffle.prize_pool(),
+            "The prize pool of the raffle is not funded"
+        );
+        let commitment: CryptoHash = raffle.commitment.into();
+        require!(
+            env::sha256_array(&secret.0) == commitment,
+            "The secret doesn't match the commitment"
+        );
+
+        let seed = env::sha256_array(&[env::random_seed(), secret.0].concat());
+        let winners = self.internal_draw_winners(&raffle, &seed);
+        for winner in winners.iter() {
+            self.internal_send_raffle_payout(
+                &raffle,
+                winner,
+                raffle.prize.0,
+                Some(format!("Prize of raffle {}", raffle_id)),
+            );
+        }
+        let leftover = raffle.prize_pool() - raffle.prize.0 * winners.len() as u128;
+        if leftover > 0 {
+            let operator = raffle.operator.clone();
+            self.internal_send_raffle_payout(
+                &raffle,
+                &operator,
+                leftover,
+                Some(format!("Leftover of raffle {}", raffle_id)),
+            );
+        }
+
+        let seed: Base58CryptoHash = seed.into();
+        raffle.status = RaffleStatus::Drawn;
+        raffle.seed = Some(seed);
+        raffle.winners = winners.clone();
+        self.raffles.insert(&raffle_id, &raffle);
+
+        RaffleDraw {
+            raffle_id,
+            seed: &seed,
+            winners: &winners,
+            prize: &raffle.prize,
+            token: &raffle.token,
+        }
+        .emit();
+        winners
+    }
+
+    /// Cancels a raffle that hasn't been drawn and refunds its prize pool to whoever funded it.
+    /// The operator and the owner of the contract can cancel it at any time, anyone else once
+    /// `RAFFLE_DRAW_TIMEOUT` has passed since its creation.
+    pub fn cancel_raffle(&mut self, raffle_id: RaffleId) -> U128 {
+        let mut raffle = self.internal_unwrap_raffle(raffle_id);
+        require!(
+            raffle.status == RaffleStatus::Registration || raffle.status == RaffleStatus::Closed,
+            "The raffle is already over"
+        );
+        let caller = env::predecessor_account_id();
+        require!(
+            caller == raffle.operator
+                || caller == self.owner_id
+                || env::block_timestamp() >= raffle.created_at.0 + RAFFLE_DRAW_TIMEOUT,
+            "Only the operator or the owner can cancel the raffle before its draw timeout"
+        );
+
+        let refund = raffle.funded;
+        raffle.status = RaffleStatus::Cancelled;
+        raffle.funded = U128(0);
+        self.raffles.insert(&raffle_id, &raffle);
+
+        if let Some(funder) = raffle.funder.clone() {
+            self.internal_send_raffle_payout(
+                &raffle,
+                &funder,
+                refund.0,
+                Some(format!("Refund of raffle {}", raffle_id)),
+            );
+        }
+        refund
+    }
+
+    /// Sends again a payout of the raffle (prize, leftover or refund) whose transfer failed.
+    pub fn retry_raffle_payout(&mut self, raffle_id: RaffleId, account_id: AccountId) {
+        let mut raffle = self.internal_unwrap_raffle(raffle_id);
+        let position = raffle
+            .unpaid
+            .iter()
+            .position(|(receiver_id, _)| *receiver_id == account_id);
+        require!(
+            position.is_some(),
+            "The raffle has no failed payout to this account"
+        );
+        let (_, amount) = raffle.unpaid.remove(position.unwrap());
+        self.raffles.insert(&raffle_id, &raffle);
+
+        self.internal_send_raffle_payout(
+            &raffle,
+            &account_id,
+            amount.0,
+            Some(format!("Payout of raffle {}", raffle_id)),
+        );
+    }
+
+    pub fn get_raffle(&self, raffle_id: RaffleId) -> Option<Raffle> {
+        self.raffles.get(&raffle_id)
+    }
+
+    pub fn get_raffle_participants(
+        &self,
+        raffle_id: RaffleId,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<AccountId> {
+        let raffle = self.internal_unwrap_raffle(raffle_id);
+        let from_index = from_index.unwrap_or(0);
+        let to_index = std::cmp::min(from_index + limit.unwrap_or(50), raffle.participants);
+        (from_index..to_index)
+            .map(|index| self.raffle_participants.get(&(raffle_id, index)).unwrap())
+            .collect()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_unwrap_raffle(&self, raffle_id: RaffleId) -> Raffle {
+        self.raffles
+            .get(&raffle_id)
+            .unwrap_or_else(|| env::panic_str("Raffle not found"))
+    }
+
+    /// Adds tokens already received by the contract from `funder` to the prize pool of a raffle.
+    pub(crate) fn internal_fund_raffle(
+        &mut self,
+        funder: &AccountId,
+        raffle_id: RaffleId,
+        token: Token,
+        amount: Balance,
+    ) {
+        let mut raffle = self.internal_unwrap_raffle(raffle_id);
+        require!(
+            raffle.token == token,
+            "The raffle is funded in another token"
+        );
+        require!(
+            raffle.status == RaffleStatus::Registration || raffle.status == RaffleStatus::Closed,
+            "The raffle is already over"
+        );
+        require!(
+            raffle.funded.0 + amount == raffle.prize_pool(),
+            "The deposit should be exactly the missing part of the prize pool"
+        );
+        raffle.funded = U128(raffle.prize_pool());
+        raffle.funder = Some(funder.clone());
+        self.raffles.insert(&raffle_id, &raffle);
+    }
+
+    pub(crate) fn internal_is_raffle_eligible(
+        &self,
+        eligibility: &RaffleEligibility,
+        account_id: &AccountId,
+    ) -> bool {
+        match eligibility {
+            RaffleEligibility::Task { task_id } => self
+                .task_participants
+                .contains(&(task_id.clone(), account_id.clone())),
+            RaffleEligibility::Event { event_id } => {
+                self.internal_unwrap_balance(account_id, event_id).is_ok()
+            }
+        }
+    }
+
+    /// Picks distinct participant indexes from successive hashes of the seed, skipping the
+    /// participants that are no longer eligible.
+    pub(crate) fn internal_draw_winners(
+        &self,
+        raffle: &Raffle,
+        seed: &CryptoHash,
+    ) -> Vec<AccountId> {
+        let mut picked = HashSet::new();
+        let mut winners = vec![];
+        let mut nonce: u64 = 0;
+        while winners.len() < raffle.winners_count as usize
+            && (picked.len() as u64) < raffle.participants
+        {
+            let hash = env::sha256_array(&[&seed[..], &nonce.to_le_bytes()[..]].concat());
+            nonce += 1;
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(&hash[..8]);
+            let index = u64::from_le_bytes(bytes) % raffle.participants;
+            if picked.insert(index) {
+                let participant = self.raffle_participants.get(&(raffle.id, index)).unwrap();
+                if self.internal_is_raffle_eligible(&raffle.eligibility, &participant) {
+                    winners.push(participant);
+                }
+            }
+        }
+        winners
+    }
+
+    /// Sends a payout of the raffle. If the transfer fails, the callback records it as unpaid.
+    pub(crate) fn internal_send_raffle_payout(
+        &mut self,
+        raffle: &Raffle,
+        receiver_id: &AccountId,
+        amount: Balance,
+        memo: Option<String>,
+    ) {
+        if let Some(promise) = self.internal_send_token(&raffle.token, receiver_id, amount, memo) {
+            promise.then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(FT_TRANSFER_GAS)
+                    .raffle_payout_callback(raffle.id, receiver_id.clone(), amount.into()),
+            );
+        }
+    }
+}
@@ -0,0 +1,487 @@
+use crate::*;
+use near_sdk::json_types::Base64VecU8;
+use near_sdk::{assert_one_yocto, ext_contract, Gas, PromiseOrValue, PromiseResult};
+use std::collections::HashMap;
+
+const GAS_FOR_NFT_RESOLVE_TRANSFER: Gas = Gas(5_000_000_000_000);
+const GAS_FOR_NFT_TRANSFER_CALL: Gas = Gas(25_000_000_000_000 + GAS_FOR_NFT_RESOLVE_TRANSFER.0);
+
+/// The specific version of the NFT metadata standard we're using
+pub const NFT_METADATA_SPEC: &str = "nft-1.0.0";
+
+/// Receipt tokens are identified by `<event_id>:<sponser_id>`.
+pub type ReceiptId = String;
+
+/// Metadata of the receipts collection (NEP-177).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct NFTContractMetadata {
+    pub spec: String,
+    pub name: String,
+    pub symbol: String,
+    pub icon: Option<String>,
+    pub base_uri: Option<String>,
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+}
+
+/// Metadata of a single receipt (NEP-177).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct TokenMetadata {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub media: Option<String>,
+    pub media_hash: Option<Base64VecU8>,
+    pub copies: Option<u64>,
+    /// Unix epoch in milliseconds
+    pub issued_at: Option<String>,
+    pub expires_at: Option<String>,
+    pub starts_at: Option<String>,
+    pub updated_at: Option<String>,
+    pub extra: Option<String>,
+    /// Metadata of the perks of the sponsor's tier
+    pub reference: Option<String>,
+    pub reference_hash: Option<Base64VecU8>,
+}
+
+/// Receipt as returned by the NEP-171 views. Receipts don't support approvals.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[serde(crate = "near_sdk::serde")]
+pub struct JsonToken {
+    pub token_id: ReceiptId,
+    pub owner_id: AccountId,
+    pub metadata: TokenMetadata,
+    pub approved_account_ids: Option<HashMap<AccountId, u64>>,
+}
+
+/// Receipt of the first sponsorship of `sponser_id` to `event_id`.
+#[derive(BorshDeserialize, BorshSerialize, Debug)]
+pub struct Receipt {
+    pub event_id: EventId,
+    pub sponser_id: AccountId,
+    pub owner_id: AccountId,
+    /// Timestamp (in nanoseconds) of the mint
+    pub issued_at: u64,
+}
+
+#[ext_contract(ext_nft_core)]
+pub trait NonFungibleTokenCore {
+    /// Transfers the receipt `token_id` from the `env::predecessor_account_id` to `receiver_id`.
+    /// Only receipts of events in transferable mode can be transferred, the others are soulbound.
+    /// Exactly 1 yoctoNEAR must be attached.
+    ///
+    /// Arguments:
+    /// - `receiver_id` - the account ID of the receiver.
+    /// - `token_id` - the receipt to transfer.
+    /// - `approval_id` - unused, receipts don't support approvals.
+    /// - `memo` - an optional string field in a free form to associate a memo with this transfer.
+    fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: ReceiptId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    );
+
+    /// Transfers the receipt `token_id` like `nft_transfer`, then calls `nft_on_transfer` on the
+    /// `receiver_id` contract and attaches a callback to resolve this transfer. The receipt goes
+    /// back to the sender if the receiver returns `true` or fails.
+    ///
+    /// Returns a promise which will result in `true` if the receipt was kept by the receiver.
+    fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: ReceiptId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool>;
+
+    /// Returns the receipt `token_id`, if it exists.
+    fn nft_token(&self, token_id: ReceiptId) -> Option<JsonToken>;
+}
+
+#[ext_contract(ext_nft_receiver)]
+pub trait NonFungibleTokenReceiver {
+    /// Called after `nft_transfer_call` moved the receipt `token_id` from `previous_owner_id`
+    /// to this contract. Returns `true` if the receipt should be given back to its previous owner.
+    fn nft_on_transfer(
+        &mut self,
+        sender_id: AccountId,
+        previous_owner_id: AccountId,
+        token_id: ReceiptId,
+        msg: String,
+    ) -> PromiseOrValue<bool>;
+}
+
+pub trait NonFungibleTokenMetadataProvider {
+    // View call for returning the metadata of the receipts collection
+    fn nft_metadata(&self) -> NFTContractMetadata;
+}
+
+pub trait NonFungibleTokenEnumeration {
+    /// Returns the number of receipts in circulation.
+    fn nft_total_supply(&self) -> U128;
+
+    /// Returns the receipts in circulation, from `from_index` (0 by default).
+    fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonToken>;
+
+    /// Returns the number of receipts owned by `account_id`.
+    fn nft_supply_for_owner(&self, account_id: AccountId) -> U128;
+
+    /// Returns the receipts owned by `account_id`, from `from_index` (0 by default).
+    fn nft_tokens_for_owner(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonToken>;
+}
+
+#[near_bindgen]
+impl NonFungibleTokenCore for Contract {
+    #[payable]
+    fn nft_transfer(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: ReceiptId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+    ) {
+        assert_one_yocto();
+        require!(approval_id.is_none(), "Receipts don't support approvals");
+        let sender_id = env::predecessor_account_id();
+        self.internal_transfer_receipt(&sender_id, &receiver_id, &token_id, memo);
+    }
+
+    #[payable]
+    fn nft_transfer_call(
+        &mut self,
+        receiver_id: AccountId,
+        token_id: ReceiptId,
+        approval_id: Option<u64>,
+        memo: Option<String>,
+        msg: String,
+    ) -> PromiseOrValue<bool> {
+        assert_one_yocto();
+        require!(approval_id.is_none(), "Receipts don't support approvals");
+        let sender_id = env::predecessor_account_id();
+        self.internal_transfer_receipt(&sender_id, &receiver_id, &token_id, memo);
+
+        ext_nft_receiver::ext(receiver_id.clone())
+            .with_static_gas(GAS_FOR_NFT_TRANSFER_CALL)
+            .nft_on_transfer(sender_id.clone(), sender_id.clone(), token_id.clone(), msg)
+            .then(
+                Self::ext(env::current_account_id())
+                    .with_static_gas(GAS_FOR_NFT_RESOLVE_TRANSFER)
+                    .nft_resolve_transfer(sender_id, receiver_id, token_id),
+            )
+            .into()
+    }
+
+    fn nft_token(&self, token_id: ReceiptId) -> Option<JsonToken> {
+        self.receipts
+            .get(&token_id)
+            .map(|receipt| self.internal_receipt_token(token_id, receipt))
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenMetadataProvider for Contract {
+    fn nft_metadata(&self) -> NFTContractMetadata {
+        NFTContractMetadata {
+            spec: NFT_METADATA_SPEC.to_string(),
+            name: "Plats sponsorship receipts".to_string(),
+            symbol: "PLATSR".to_string(),
+            icon: Some(DATA_IMAGE_SVG_NEAR_ICON.to_string()),
+            base_uri: None,
+            reference: None,
+            reference_hash: None,
+        }
+    }
+}
+
+#[near_bindgen]
+impl NonFungibleTokenEnumeration for Contract {
+    fn nft_total_supply(&self) -> U128 {
+        U128(self.receipt_ids.len() as u128)
+    }
+
+    fn nft_tokens(&self, from_index: Option<U128>, limit: Option<u64>) -> Vec<JsonToken> {
+        let from_index = from_index.map(|index| index.0).unwrap_or(0) as usize;
+        let limit = limit.unwrap_or(self.receipt_ids.len()) as usize;
+        self.receipt_ids
+            .iter()
+            .skip(from_index)
+            .take(limit)
+            .map(|token_id| {
+                let receipt = self.receipts.get(&token_id).unwrap();
+                self.internal_receipt_token(token_id, receipt)
+            })
+            .collect()
+    }
+
+    fn nft_supply_for_owner(&self, account_id: AccountId) -> U128 {
+        self.receipts_per_owner
+            .get(&account_id)
+            .map(|token_ids| U128(token_ids.len() as u128))
+            .unwrap_or(U128(0))
+    }
+
+    fn nft_tokens_for_owner(
+        &self,
+        account_id: AccountId,
+        from_index: Option<U128>,
+        limit: Option<u64>,
+    ) -> Vec<JsonToken> {
+        let token_ids = match self.receipts_per_owner.get(&account_id) {
+            Some(token_ids) => token_ids,
+            None => return vec![],
+        };
+        let from_index = from_index.map(|index| index.0).unwrap_or(0) as usize;
+        let limit = limit.unwrap_or(token_ids.len()) as usize;
+        token_ids
+            .iter()
+            .skip(from_index)
+            .take(limit)
+            .map(|token_id| {
+                let receipt = self.receipts.get(&token_id).unwrap();
+                self.internal_receipt_token(token_id, receipt)
+            })
+            .collect()
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Lets the receipts of the event be transferred, or makes them soulbound again.
+    pub fn set_receipts_transferable(&mut self, event_id: EventId, transferable: bool) {
+        self.internal_watch_detail_event(&event_id);
+        require!(
+            self.check_owner_event(&event_id),
+            "Only the owner of the event can change its receipts"
+        );
+        if transferable {
+            self.transferable_receipts.insert(&event_id);
+        } else {
+            self.transferable_receipts.remove(&event_id);
+        }
+    }
+
+    pub fn get_receipts_transferable(&self, event_id: EventId) -> bool {
+        self.transferable_receipts.contains(&event_id)
+    }
+
+    /// Returns the receipt of the sponsorship of `account_id` to the event, if any.
+    pub fn get_sponsorship_receipt(
+        &self,
+        event_id: EventId,
+        account_id: AccountId,
+    ) -> Option<JsonToken> {
+        self.nft_token(receipt_id(&event_id, &account_id))
+    }
+
+    // Resolves the promise chain of `nft_transfer_call`: the receipt goes back to the previous
+    // owner if the receiver asked for it or failed, and it still holds the receipt.
+    //
+    // Returns `true` if the receipt was kept by the receiver.
+    #[private]
+    pub fn nft_resolve_transfer(
+        &mut self,
+        previous_owner_id: AccountId,
+        receiver_id: AccountId,
+        token_id: ReceiptId,
+    ) -> bool {
+        let must_revert = match env::promise_result(0) {
+            PromiseResult::NotReady => env::abort(),
+            PromiseResult::Successful(value) => {
+                near_sdk::serde_json::from_slice::<bool>(&value).unwrap_or(true)
+            }
+            PromiseResult::Failed => true,
+        };
+        if !must_revert {
+            return true;
+        }
+
+        // The receipt may have been burned or moved again in the meantime
+        match self.receipts.get(&token_id) {
+            Some(receipt) if receipt.owner_id == receiver_id => {
+                self.internal_move_receipt(receipt, &token_id, &previous_owner_id);
+                NftTransfer {
+                    old_owner_id: &receiver_id,
+                    new_owner_id: &previous_owner_id,
+                    token_ids: &[token_id.as_str()],
+                    memo: None,
+                }
+                .emit();
+                false
+            }
+            _ => true,
+        }
+    }
+}
+
+pub(crate) fn receipt_id(event_id: &EventId, sponser_id: &AccountId) -> ReceiptId {
+    format!("{}:{}", event_id, sponser_id)
+}
+
+impl Contract {
+    /// Mints the receipt of the first sponsorship of `sponser_id` to the event. A sponsor keeps
+    /// a single receipt per event, even when sponsoring it again.
+    pub(crate) fn internal_mint_receipt(&mut self, sponser_id: &AccountId, event_id: &EventId) {
+        let token_id = receipt_id(event_id, sponser_id);
+        if self.receipts.contains_key(&token_id) {
+            return;
+        }
+        let receipt = Receipt {
+            event_id: event_id.clone(),
+            sponser_id: sponser_id.clone(),
+            owner_id: sponser_id.clone(),
+            issued_at: env::block_timestamp(),
+        };
+        self.receipts.insert(&token_id, &receipt);
+        self.receipt_ids.insert(&token_id);
+        self.internal_add_receipt_to_owner(sponser_id, &token_id);
+
+        NftMint {
+            owner_id: sponser_id,
+            token_ids: &[token_id.as_str()],
+            memo: None,
+        }
+        .emit();
+    }
+
+    /// Burns the receipt of the sponsorship of `sponser_id` to the event, whoever holds it.
+    pub(crate) fn internal_burn_receipt(
+        &mut self,
+        sponser_id: &AccountId,
+        event_id: &EventId,
+        memo: &str,
+    ) {
+        let token_id = receipt_id(event_id, sponser_id);
+        let receipt = match self.receipts.remove(&token_id) {
+            Some(receipt) => receipt,
+            None => return,
+        };
+        self.receipt_ids.remove(&token_id);
+        self.internal_remove_receipt_from_owner(&receipt.owner_id, &token_id);
+
+        NftBurn {
+            owner_id: &receipt.owner_id,
+            token_ids: &[token_id.as_str()],
+            memo: Some(memo),
+        }
+        .emit();
+    }
+
+    pub(crate) fn internal_transfer_receipt(
+        &mut self,
+        sender_id: &AccountId,
+        receiver_id: &AccountId,
+        token_id: &ReceiptId,
+        memo: Option<String>,
+    ) {
+        let receipt = self
+            .receipts
+            .get(token_id)
+            .unwrap_or_else(|| env::panic_str("Receipt not found"));
+        require!(
+            receipt.owner_id == *sender_id,
+            "Only the owner of the receipt can transfer it"
+        );
+        require!(
+            sender_id != receiver_id,
+            "Sender and receiver should be different"
+        );
+        require!(
+            self.transferable_receipts.contains(&receipt.event_id),
+            "The receipts of this event are soulbound"
+        );
+        self.internal_move_receipt(receipt, token_id, receiver_id);
+
+        NftTransfer {
+            old_owner_id: sender_id,
+            new_owner_id: receiver_id,
+            token_ids: &[token_id.as_str()],
+            memo: memo.as_deref(),
+        }
+        .emit();
+    }
+
+    fn internal_move_receipt(
+        &mut self,
+        mut receipt: Receipt,
+        token_id: &ReceiptId,
+        receiver_id: &AccountId,
+    ) {
+        self.internal_remove_receipt_from_owner(&receipt.owner_id, token_id);
+        self.internal_add_receipt_to_owner(receiver_id, token_id);
+        receipt.owner_id = receiver_id.clone();
+        self.receipts.insert(token_id, &receipt);
+    }
+
+    fn internal_add_receipt_to_owner(&mut self, account_id: &AccountId, token_id: &ReceiptId) {
+        let mut token_ids = self.receipts_per_owner.get(account_id).unwrap_or_else(|| {
+            UnorderedSet::new(
+                Prefix::ReceiptsOfOwner {
+                    account_hash: env::sha256(account_id.as_bytes()),
+                }
+                .try_to_vec()
+                .unwrap(),
+            )
+        });
+        token_ids.insert(token_id);
+        self.receipts_per_owner.insert(account_id, &token_ids);
+    }
+
+    fn internal_remove_receipt_from_owner(&mut self, account_id: &AccountId, token_id: &ReceiptId) {
+        if let Some(mut token_ids) = self.receipts_per_owner.get(account_id) {
+            token_ids.remove(token_id);
+            if token_ids.is_empty() {
+                self.receipts_per_owner.remove(account_id);
+            } else {
+                self.receipts_per_owner.insert(account_id, &token_ids);
+            }
+        }
+    }
+
+    /// Builds the receipt token, with metadata taken from the event and the current tier of
+    /// the sponsorship.
+    fn internal_receipt_token(&self, token_id: ReceiptId, receipt: Receipt) -> JsonToken {
+        let event = self.events.get(&receipt.event_id).unwrap();
+        let tier = self.get_sponsor_tier(receipt.event_id.clone(), receipt.sponser_id.clone());
+        let title = match &tier {
+            Some(tier) => format!("{} - {} sponsor", event.name, tier.name),
+            None => format!("{} - Sponsor", event.name),
+        };
+        JsonToken {
+            token_id,
+            owner_id: receipt.owner_id,
+            metadata: TokenMetadata {
+                title: Some(title),
+                description: Some(format!(
+                    "Receipt of the sponsorship of {} to the event {}",
+                    receipt.sponser_id, event.name
+                )),
+                media: None,
+                media_hash: None,
+                copies: Some(1),
+                issued_at: Some((receipt.issued_at / 1_000_000).to_string()),
+                expires_at: None,
+                starts_at: None,
+                updated_at: None,
+                extra: Some(
+                    near_sdk::serde_json::json!({
+                        "event_id": receipt.event_id,
+                        "tier": tier.as_ref().map(|tier| &tier.name),
+                    })
+                    .to_string(),
+                ),
+                reference: tier.map(|tier| tier.perks_uri),
+                reference_hash: None,
+            },
+            approved_account_ids: None,
+        }
+    }
+}
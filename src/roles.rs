@@ -0,0 +1,169 @@
+use crate::*;
+use std::collections::HashMap;
+
+/// Role of a co-organizer of an event. Admins can do everything editors and finance can.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum EventRole {
+    /// Can cancel the event
+    Admin,
+    /// Can update the metadata of the event
+    Editor,
+    /// Can handle the funds of the event: submit the prize winners, request milestone releases,
+    /// settle its fees and withdraw its unpaid settlements
+    Finance,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Grants `role` to `account_id` on the event, replacing their previous role.
+    /// The attached deposit covers the storage of the role, the rest is refunded.
+    #[payable]
+    pub fn grant_event_role(&mut self, event_id: EventId, account_id: AccountId, role: EventRole) {
+        let init_storage = env::storage_usage();
+        let event = self.internal_watch_detail_event(&event_id);
+        require!(
+            self.check_owner_event(&event_id),
+            "Only the owner of the event can grant roles"
+        );
+        require!(
+            account_id != event.owner,
+            "The owner already has every role"
+        );
+        let mut managers = self.event_managers.get(&event_id).unwrap_or_default();
+        managers.insert(account_id, role);
+        self.event_managers.insert(&event_id, &managers);
+        refund_deposit(init_storage);
+    }
+
+    pub fn revoke_event_role(&mut self, event_id: EventId, account_id: AccountId) {
+        require!(
+            self.check_owner_event(&event_id),
+            "Only the owner of the event can revoke roles"
+        );
+        let mut managers = self.event_managers.get(&event_id).unwrap_or_default();
+        require!(
+            managers.remove(&account_id).is_some(),
+            "The account has no role on this event"
+        );
+        if managers.is_empty() {
+            self.event_managers.remove(&event_id);
+        } else {
+            self.event_managers.insert(&event_id, &managers);
+        }
+    }
+
+    /// Updates the metadata of the event. The owner, editors and admins can update it.
+    pub fn update_event(&mut self, event_id: EventId, name_event: String) -> Event {
+        let mut event = self.internal_watch_detail_event(&event_id);
+        require!(
+            self.check_event_role(&event_id, EventRole::Editor),
+            "You are not allowed to update this event"
+        );
+        event.name = name_event;
+        self.events.insert(&event_id, &event);
+        event
+    }
+
+    /// First step of the transfer of the event: the owner proposes a new owner, who then has
+    /// to accept it. Proposing `None` withdraws the proposal.
+    /// The attached deposit covers the storage of the proposal, the rest is refunded.
+    #[payable]
+    pub fn propose_event_owner(&mut self, event_id: EventId, new_owner_id: Option<AccountId>) {
+        let init_storage = env::storage_usage();
+        let event = self.internal_watch_detail_event(&event_id);
+        require!(
+            self.check_owner_event(&event_id),
+            "Only the owner of the event can transfer it"
+        );
+        match new_owner_id {
+            Some(new_owner_id) => {
+                require!(
+                    new_owner_id != event.owner,
+                    "The account already owns the event"
+                );
+                self.pending_event_owners.insert(&event_id, &new_owner_id);
+            }
+            None => {
+                self.pending_event_owners.remove(&event_id);
+            }
+        }
+        refund_deposit(init_storage);
+    }
+
+    /// Second step of the transfer of the event: the proposed owner takes it over.
+    pub fn accept_event_ownership(&mut self, event_id: EventId) {
+        let new_owner_id = env::predecessor_account_id();
+        let mut event = self.internal_watch_detail_event(&event_id);
+        require!(
+            self.pending_event_owners.get(&event_id) == Some(new_owner_id.clone()),
+            "You haven't been proposed as the owner of this event"
+        );
+        require!(
+            self.event_arbitrators.get(&event_id) != Some(new_owner_id.clone()),
+            "The owner can't arbitrate their own event"
+        );
+        self.pending_event_owners.remove(&event_id);
+        let old_owner_id = std::mem::replace(&mut event.owner, new_owner_id.clone());
+        self.events.insert(&event_id, &event);
+        self.internal_track_owner_change(&event, &old_owner_id);
+
+        // The event moves to the events of the new owner
+        if let Some(mut res) = self.client_to_event_id.get(&old_owner_id) {
+            res.events.remove(&event_id);
+            if res.events.is_empty() {
+                self.client_to_event_id.remove(&old_owner_id);
+            } else {
+                self.client_to_event_id.insert(&old_owner_id, &res);
+            }
+        }
+        let mut res = self
+            .client_to_event_id
+            .get(&new_owner_id)
+            .unwrap_or(ClientEvent {
+                events: HashSet::new(),
+            });
+        res.events.insert(event_id.clone());
+        self.client_to_event_id.insert(&new_owner_id, &res);
+
+        // The new owner no longer needs a role
+        if let Some(mut managers) = self.event_managers.get(&event_id) {
+            if managers.remove(&new_owner_id).is_some() {
+                self.event_managers.insert(&event_id, &managers);
+            }
+        }
+
+        EventOwnerChange {
+            event_id: &event_id,
+            old_owner_id: &old_owner_id,
+            new_owner_id: &new_owner_id,
+        }
+        .emit();
+    }
+
+    pub fn get_event_managers(&self, event_id: EventId) -> HashMap<AccountId, EventRole> {
+        self.event_managers.get(&event_id).unwrap_or_default()
+    }
+
+    pub fn get_pending_event_owner(&self, event_id: EventId) -> Option<AccountId> {
+        self.pending_event_owners.get(&event_id)
+    }
+}
+
+impl Contract {
+    /// Whether the signer is the owner of the event, an admin of it, or has `role` on it.
+    pub(crate) fn check_event_role(&self, event_id: &EventId, role: EventRole) -> bool {
+        if self.check_owner_event(event_id) {
+            return true;
+        }
+        match self
+            .event_managers
+            .get(event_id)
+            .and_then(|managers| managers.get(&env::signer_account_id()).cloned())
+        {
+            Some(EventRole::Admin) => true,
+            Some(granted) => granted == role,
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,344 @@
+use ed25519_dalek::Verifier;
+use near_sdk::json_types::{Base64VecU8, U64};
+use near_sdk::{CurveType, PublicKey};
+
+use crate::external::ext_ft_fungible_token;
+use crate::*;
+
+/// The payload the backend signs to authorize a task reward. It is Borsh-serialized
+/// and the signature is checked against the registered task signer key.
+#[derive(BorshSerialize)]
+pub struct TaskRewardPayload {
+    /// This contract, so an authorization can't be replayed against another deployment.
+    pub contract_id: AccountId,
+    pub task_id: TaskId,
+    pub receiver_id: AccountId,
+    pub amount: Balance,
+    pub nonce: u64,
+    /// Timestamp (in nanoseconds) after which the authorization is no longer valid.
+    pub expiry: u64,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Creates a task owned by the caller, paid in PLAT unless `token` says USDT. The task is then
+    /// funded by `ft_transfer_call` of that token to this contract with the message `{"task_id": "<task_id>"}`.
+    /// The attached deposit covers the storage of the task, the rest is refunded.
+    #[payable]
+    pub fn create_task(
+        &mut self,
+        task_id: TaskId,
+        reward: U128,
+        max_participants: u64,
+        expires_at: U64,
+        token: Option<Token>,
+    ) -> TaskInfo {
+        let init_storage = env::storage_usage();
+        let client = env::predecessor_account_id();
+        let token = token.unwrap_or(Token::PLAT);
+        require!(
+            token != Token::NEAR,
+            "Tasks can only be funded in PLAT or USDT"
+        );
+        require!(!self.tasks.contains_key(&task_id), "TaskId already exists");
+        require!(reward.0 > 0, "The reward should be a positive number");
+        require!(
+            max_participants > 0,
+            "The task should accept at least one participant"
+        );
+        require!(
+            expires_at.0 > env::block_timestamp(),
+            "The expiry should be in the future"
+        );
+
+        let task_info = TaskInfo {
+            client,
+            token,
+            amount: U128(0),
+            reward,
+            max_participants,
+            participants: 0,
+            expires_at,
+            status: TaskStatus::Active,
+        };
+        self.tasks.insert(&task_id, &task_info);
+        self.list_task.insert(&task_id);
+        // The budget is held by the contract's own account
+        self.internal_register_contract_account();
+
+        refund_deposit(init_storage);
+        task_info
+    }
+
+    /// Pays the task reward to `account_id` for completing `task_id`.
+    /// Only the client of the task or the owner of the contract can confirm a completion.
+    pub fn complete_task(&mut self, task_id: TaskId, account_id: AccountId) -> U128 {
+        let task_info = self.internal_unwrap_task(&task_id);
+        let caller = env::predecessor_account_id();
+        require!(
+            caller == task_info.client || caller == self.owner_id,
+            "You are not allowed to confirm completions of this task"
+        );
+        self.internal_pay_task_reward(&task_id, &account_id, task_info.reward.0);
+        task_info.reward
+    }
+
+    /// Registers the ed25519 key the backend uses to sign task reward authorizations.
+    pub fn set_task_signer(&mut self, public_key: PublicKey) {
+        self.assert_owner();
+        require!(
+            public_key.curve_type() == CurveType::ED25519,
+            "The task signer key should be an ed25519 key"
+        );
+        self.task_signer = Some(public_key);
+    }
+
+    pub fn get_task_signer(&self) -> Option<PublicKey> {
+        self.task_signer.clone()
+    }
+
+    /// Pays the caller `amount` from the budget of `task_id`, as authorized by the backend's signature
+    /// over the Borsh-serialized [`TaskRewardPayload`]. Each nonce can only be used once, whatever
+    /// the task and the receiver it was signed for, so the backend must never sign the same nonce twice.
+    pub fn claim_task_reward(
+        &mut self,
+        task_id: TaskId,
+        amount: U128,
+        nonce: U64,
+        expiry: U64,
+        signature: Base64VecU8,
+    ) -> U128 {
+        let receiver_id = env::predecessor_account_id();
+        require!(
+            env::block_timestamp() < expiry.0,
+            "The reward authorization has expired"
+        );
+        require!(
+            !self.used_task_nonces.contains(&nonce.0),
+            "The reward authorization has already been used"
+        );
+
+        let payload = TaskRewardPayload {
+            contract_id: env::current_account_id(),
+            task_id: task_id.clone(),
+            receiver_id: receiver_id.clone(),
+            amount: amount.0,
+            nonce: nonce.0,
+            expiry: expiry.0,
+        };
+        require!(
+            self.verify_task_signature(&payload.try_to_vec().unwrap(), &signature.0),
+            "Invalid reward authorization signature"
+        );
+        self.used_task_nonces.insert(&nonce.0);
+
+        self.internal_pay_task_reward(&task_id, &receiver_id, amount.0);
+        amount
+    }
+
+    pub fn is_task_nonce_used(&self, nonce: U64) -> bool {
+        self.used_task_nonces.contains(&nonce.0)
+    }
+
+    pub fn get_task(&self, task_id: TaskId) -> Option<TaskInfo> {
+        self.tasks.get(&task_id)
+    }
+
+    pub fn get_tasks(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(TaskId, TaskInfo)> {
+        self.list_task
+            .iter()
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .map(|task_id| {
+                let task_info = self.tasks.get(&task_id).unwrap();
+                (task_id, task_info)
+            })
+            .collect()
+    }
+
+    pub fn has_completed_task(&self, task_id: TaskId, account_id: AccountId) -> bool {
+        self.task_participants.contains(&(task_id, account_id))
+    }
+
+    /// Closes an expired task and refunds its remaining budget to the client, in the token
+    /// the task was funded with. No reward can be claimed from a closed task.
+    pub fn close_task(&mut self, task_id: TaskId) -> U128 {
+        let mut task_info = self.internal_unwrap_task(&task_id);
+        let caller = env::predecessor_account_id();
+        require!(
+            caller == task_info.client || caller == self.owner_id,
+            "You are not allowed to close this task"
+        );
+        require!(
+            task_info.status == TaskStatus::Active,
+            "The task is already closed"
+        );
+        require!(
+            env::block_timestamp() >= task_info.expires_at.0,
+            "The task has not expired yet"
+        );
+
+        let refund = task_info.amount;
+        task_info.amount = U128(0);
+        task_info.status = TaskStatus::Closed;
+        self.tasks.insert(&task_id, &task_info);
+
+        if refund.0 > 0 {
+            if task_info.token == Token::PLAT {
+                self.internal_unlock(
+                    &task_info.client,
+                    refund.0,
+                    Some(format!("Refund of task {}", task_id)),
+                );
+            } else {
+                ext_ft_fungible_token::ext(USDT_TOKEN_ID.parse().unwrap())
+                    .with_attached_deposit(1)
+                    .with_static_gas(FT_TRANSFER_GAS)
+                    .ft_transfer(task_info.client.clone(), refund, None)
+                    .then(
+                        ext_self::ext(env::current_account_id())
+                            .with_static_gas(FT_TRANSFER_GAS)
+                            .task_refund_callback(task_id.clone(), refund),
+                    );
+            }
+        }
+
+        TaskClosed {
+            task_id: &task_id,
+            client_id: &task_info.client,
+            refund: &refund,
+            token: &task_info.token,
+        }
+        .emit();
+        refund
+    }
+
+    /// Returns the tasks that have expired but still hold some budget, which `close_task` can refund.
+    pub fn get_expired_tasks_with_budget(
+        &self,
+        from_index: Option<u64>,
+        limit: Option<u64>,
+    ) -> Vec<(TaskId, TaskInfo)> {
+        let now = env::block_timestamp();
+        self.list_task
+            .iter()
+            .filter_map(|task_id| {
+                let task_info = self.tasks.get(&task_id).unwrap();
+                if task_info.status == TaskStatus::Active
+                    && now >= task_info.expires_at.0
+                    && task_info.amount.0 > 0
+                {
+                    Some((task_id, task_info))
+                } else {
+                    None
+                }
+            })
+            .skip(from_index.unwrap_or(0) as usize)
+            .take(limit.unwrap_or(50) as usize)
+            .collect()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_unwrap_task(&self, task_id: &TaskId) -> TaskInfo {
+        self.tasks
+            .get(task_id)
+            .unwrap_or_else(|| env::panic_str("TaskId not exist"))
+    }
+
+    /// Adds tokens already transferred to this contract to the budget of a task.
+    pub(crate) fn internal_fund_task(
+        &mut self,
+        sender_id: &AccountId,
+        task_id: &TaskId,
+        token: Token,
+        amount: Balance,
+    ) {
+        let mut task_info = self.internal_unwrap_task(task_id);
+        require!(
+            *sender_id == task_info.client,
+            "Only the client of the task can fund it"
+        );
+        require!(
+            token == task_info.token,
+            "The task is funded in another token"
+        );
+        require!(task_info.status == TaskStatus::Active, "The task is closed");
+        if let Some(new_amount) = task_info.amount.0.checked_add(amount) {
+            task_info.amount = U128(new_amount);
+        } else {
+            env::panic_str("Balance overflow");
+        }
+        self.tasks.insert(task_id, &task_info);
+    }
+
+    /// Pays `amount` from the budget of a task to an account that completed it.
+    /// Each account is rewarded at most once per task.
+    pub(crate) fn internal_pay_task_reward(
+        &mut self,
+        task_id: &TaskId,
+        receiver_id: &AccountId,
+        amount: Balance,
+    ) {
+        let mut task_info = self.internal_unwrap_task(task_id);
+        require!(
+            task_info.status == TaskStatus::Active
+                && env::block_timestamp() < task_info.expires_at.0,
+            "The task is not active"
+        );
+        require!(
+            task_info.participants < task_info.max_participants,
+            "The task has reached its maximum number of participants"
+        );
+        require!(
+            self.task_participants
+                .insert(&(task_id.clone(), receiver_id.clone())),
+            "The account has already been rewarded for this task"
+        );
+
+        if let Some(remaining) = task_info.amount.0.checked_sub(amount) {
+            task_info.amount = U128(remaining);
+        } else {
+            env::panic_str("The task budget is not enough for this reward");
+        }
+        task_info.participants += 1;
+        self.tasks.insert(task_id, &task_info);
+
+        if task_info.token == Token::PLAT {
+            self.internal_unlock(
+                receiver_id,
+                amount,
+                Some(format!("Reward for task {}", task_id)),
+            );
+        } else {
+            // The callback gives the reward back to the task if the transfer fails
+            ext_ft_fungible_token::ext(USDT_TOKEN_ID.parse().unwrap())
+                .with_attached_deposit(1)
+                .with_static_gas(FT_TRANSFER_GAS)
+                .ft_transfer(receiver_id.clone(), amount.into(), None)
+                .then(
+                    ext_self::ext(env::current_account_id())
+                        .with_static_gas(FT_TRANSFER_GAS)
+                        .task_payout_callback(task_id.clone(), receiver_id.clone(), amount.into()),
+                );
+        }
+    }
+
+    pub(crate) fn verify_task_signature(&self, message: &[u8], signature: &[u8]) -> bool {
+        let signer = self
+            .task_signer
+            .as_ref()
+            .unwrap_or_else(|| env::panic_str("The task signer is not set"));
+        // The first byte of a NEAR public key is its curve type
+        let key = ed25519_dalek::PublicKey::from_bytes(&signer.as_bytes()[1..])
+            .unwrap_or_else(|_| env::panic_str("The task signer key is invalid"));
+        match ed25519_dalek::Signature::try_from(signature) {
+            Ok(signature) => key.verify(message, &signature).is_ok(),
+            Err(_) => false,
+        }
+    }
+}
@@ -1,10 +1,30 @@
 #[cfg(all(test, not(target_arch = "wasm32")))]
 mod tests {
-    use crate::event::{Amount, Event};
-    use crate::Contract;
-    use near_sdk::json_types::U128;
-    use near_sdk::test_utils::{accounts, VMContextBuilder};
-    use near_sdk::{env, log, testing_env, AccountId, VMContext};
+    use crate::airdrop::airdrop_leaf_hash;
+    use crate::dispute::Ruling;
+    use crate::event::{Amount, Event, Token};
+    use crate::ft_core::{BatchTransferStatus, FungibleTokenCore, FungibleTokenReceiver};
+    use crate::leaderboard::{LeaderboardEntry, Ranking};
+    use crate::ledger::ContributionKind;
+    use crate::limits::{SponsorshipLimits, TokenLimits};
+    use crate::milestone::MilestoneStatus;
+    use crate::oracle::{AssetOptionalPrice, Price, PriceData, PriceOracleConfig, UsdValue};
+    use crate::prize::{PayoutStatus, PrizeShare, PrizeWinner};
+    use crate::quest::QuestStep;
+    use crate::raffle::RaffleEligibility;
+    use crate::receipt::{NonFungibleTokenCore, NonFungibleTokenEnumeration};
+    use crate::roles::EventRole;
+    use crate::task::TaskRewardPayload;
+    use crate::tier::SponsorTier;
+    use crate::withdrawal::{PenaltyReceiver, WithdrawalPolicy};
+    use crate::{Contract, TaskInfo, TaskStatus};
+    use near_sdk::borsh::BorshSerialize;
+    use near_sdk::json_types::{U128, U64};
+    use near_sdk::test_utils::{accounts, get_logs, VMContextBuilder};
+    use near_sdk::{env, testing_env, PromiseOrValue, PromiseResult, PublicKey, VMContext};
+
+    /// Covers the storage of a sponsorship on top of its amount.
+    const STORAGE_DEPOSIT: u128 = 10u128.pow(23);
 
     fn get_context() -> VMContext {
         let mut builder = VMContextBuilder::new();
@@ -23,7 +43,7 @@ mod tests {
         context.attached_deposit = 1;
         context.signer_account_id = accounts(2);
         testing_env!(context);
-        let mut contract = Contract::new(accounts(1));
+        let mut contract = Contract::new_default_meta(U128(1_000));
         contract.create_event(String::from("001"), String::from("Panana"));
         contract.create_event(String::from("002"), String::from("AHAHA"));
         let event = Event {
@@ -49,7 +69,7 @@ mod tests {
         context.attached_deposit = 1;
         context.signer_account_id = accounts(2);
         testing_env!(context);
-        let mut contract = Contract::new(accounts(1));
+        let mut contract = Contract::new_default_meta(U128(1_000));
         contract.create_event(String::from("001"), String::from("Panana"));
         contract.create_event(String::from("002"), String::from("AHAHA"));
         contract.create_event(String::from("006"), String::from("AHAHA6"));
@@ -64,7 +84,7 @@ mod tests {
         context.attached_deposit = 1;
         context.signer_account_id = accounts(2);
         testing_env!(context.clone());
-        let mut contract = Contract::new(accounts(1));
+        let mut contract = Contract::new_default_meta(U128(1_000));
         contract.create_event(String::from("001"), String::from("Panana"));
         contract.create_event(String::from("002"), String::from("AHAHA"));
 
@@ -84,26 +104,26 @@ mod tests {
     #[test]
     fn test_sponse_native() {
         let mut context = get_context();
-        context.attached_deposit = 5_000;
+        context.attached_deposit = 5_000 + STORAGE_DEPOSIT;
         context.signer_account_id = accounts(2);
         testing_env!(context.clone());
-        let mut contract = Contract::new(accounts(1));
+        let mut contract = Contract::new_default_meta(U128(1_000));
 
         contract.create_event(String::from("001"), String::from("Panana"));
         contract.create_event(String::from("002"), String::from("AHAHA"));
         contract.create_event(String::from("003"), String::from("AHAHA"));
 
-        contract.sponse_native(String::from("001"), U128(5000));
-        contract.sponse_native(String::from("002"), U128(5000));
-        contract.sponse_native(String::from("003"), U128(5000));
+        contract.sponse_native(String::from("001"), U128(5000), None);
+        contract.sponse_native(String::from("002"), U128(5000), None);
+        contract.sponse_native(String::from("003"), U128(5000), None);
 
         context.signer_account_id = accounts(0);
         testing_env!(context.clone());
-        contract.sponse_native(String::from("001"), U128(5000));
+        contract.sponse_native(String::from("001"), U128(5000), None);
 
         context.signer_account_id = accounts(1);
         testing_env!(context);
-        contract.sponse_native(String::from("001"), U128(5000));
+        contract.sponse_native(String::from("001"), U128(5000), None);
 
         // so sánh số lượng các event_id mà sponser tham gia.
         assert_eq!(
@@ -130,16 +150,16 @@ mod tests {
     #[test] // test ham get_sponsed
     fn test_get_sponsed() {
         let mut context = get_context();
-        context.attached_deposit = 5_000;
+        context.attached_deposit = 5_000 + STORAGE_DEPOSIT;
         context.signer_account_id = accounts(2);
         testing_env!(context.clone());
-        let mut contract = Contract::new(accounts(1));
+        let mut contract = Contract::new_default_meta(U128(1_000));
         contract.create_event(String::from("001"), String::from("Panana"));
         contract.create_event(String::from("002"), String::from("AHAHA"));
         contract.create_event(String::from("003"), String::from("AHAHA"));
-        contract.sponse_native(String::from("001"), U128(5000));
-        contract.sponse_native(String::from("002"), U128(5000));
-        contract.sponse_native(String::from("003"), U128(5000));
+        contract.sponse_native(String::from("001"), U128(5000), None);
+        contract.sponse_native(String::from("002"), U128(5000), None);
+        contract.sponse_native(String::from("003"), U128(5000), None);
 
         assert_eq!(contract.get_sponsed().len(), 3);
     }
@@ -147,24 +167,24 @@ mod tests {
     #[test]
     fn test_get_all_sponser_event() {
         let mut context = get_context();
-        context.attached_deposit = 5_000;
+        context.attached_deposit = 5_000 + STORAGE_DEPOSIT;
 
         context.signer_account_id = accounts(2);
         testing_env!(context.clone());
-        let mut contract = Contract::new(accounts(1));
+        let mut contract = Contract::new_default_meta(U128(1_000));
         contract.create_event(String::from("001"), String::from("Panana"));
         contract.create_event(String::from("002"), String::from("AHAHA"));
         contract.create_event(String::from("003"), String::from("AHAHA"));
 
-        contract.sponse_native(String::from("001"), U128(5000));
+        contract.sponse_native(String::from("001"), U128(5000), None);
 
         context.signer_account_id = accounts(0);
         testing_env!(context.clone());
-        contract.sponse_native(String::from("001"), U128(5000));
+        contract.sponse_native(String::from("001"), U128(5000), None);
 
         context.signer_account_id = accounts(1);
         testing_env!(context);
-        contract.sponse_native(String::from("001"), U128(5000));
+        contract.sponse_native(String::from("001"), U128(5000), None);
 
         assert_eq!(contract.get_all_sponser_event(String::from("001")).len(), 3);
     }
@@ -172,25 +192,25 @@ mod tests {
     #[test]
     fn test_get_total_token_event() {
         let mut context = get_context();
-        context.attached_deposit = 5_000;
+        context.attached_deposit = 5_000 + STORAGE_DEPOSIT;
 
         context.signer_account_id = accounts(2);
         testing_env!(context.clone());
-        let mut contract = Contract::new(accounts(1));
+        let mut contract = Contract::new_default_meta(U128(1_000));
         contract.create_event(String::from("001"), String::from("Panana"));
         contract.create_event(String::from("002"), String::from("AHAHA"));
         contract.create_event(String::from("003"), String::from("AHAHA"));
 
-        contract.sponse_native(String::from("001"), U128(5000));
+        contract.sponse_native(String::from("001"), U128(5000), None);
 
         context.signer_account_id = accounts(0);
         testing_env!(context.clone());
-        contract.sponse_native(String::from("001"), U128(5000));
+        contract.sponse_native(String::from("001"), U128(5000), None);
 
         context.signer_account_id = accounts(1);
         testing_env!(context);
-        contract.sponse_native(String::from("001"), U128(5000));
-        contract.more_sponse_usdt(String::from("001"), U128(2000));
+        contract.sponse_native(String::from("001"), U128(5000), None);
+        contract.internal_more_sponse_usdt(&accounts(1), &String::from("001"), 2000, None);
 
         assert_eq!(
             contract.get_total_token_event(&String::from("001")),
@@ -207,7 +227,7 @@ mod tests {
         context.attached_deposit = 1;
         context.signer_account_id = accounts(2);
         testing_env!(context.clone());
-        let mut contract = Contract::new(accounts(1));
+        let mut contract = Contract::new_default_meta(U128(1_000));
         contract.create_event(String::from("001"), String::from("Panana"));
         contract.create_event(String::from("002"), String::from("AHAHA"));
         contract.create_event(String::from("006"), String::from("AHAHA6"));
@@ -225,7 +245,7 @@ mod tests {
         context.attached_deposit = 1;
         context.signer_account_id = accounts(2);
         testing_env!(context.clone());
-        let mut contract = Contract::new(accounts(1));
+        let mut contract = Contract::new_default_meta(U128(1_000));
         contract.create_event(String::from("001"), String::from("Panana"));
         contract.create_event(String::from("002"), String::from("AHAHA"));
         contract.create_event(String::from("006"), String::from("AHAHA6"));
@@ -240,24 +260,24 @@ mod tests {
     #[test]
     fn test_more_sponse_native() {
         let mut context = get_context();
-        context.attached_deposit = 5_000;
+        context.attached_deposit = 5_000 + STORAGE_DEPOSIT;
         context.signer_account_id = accounts(2);
         testing_env!(context.clone());
-        let mut contract = Contract::new(accounts(1));
+        let mut contract = Contract::new_default_meta(U128(1_000));
 
         contract.create_event(String::from("001"), String::from("Panana"));
         contract.create_event(String::from("002"), String::from("AHAHA"));
         contract.create_event(String::from("003"), String::from("AHAHA"));
 
-        contract.sponse_native(String::from("001"), U128(5000));
-        // contract.sponse_native(String::from("002"), U128(5000));
-        // contract.sponse_native(String::from("003"), U128(5000));
+        contract.sponse_native(String::from("001"), U128(5000), None);
+        // contract.sponse_native(String::from("002"), U128(5000), None);
+        // contract.sponse_native(String::from("003"), U128(5000), None);
 
-        context.attached_deposit = 20000;
+        context.attached_deposit = 20000 + STORAGE_DEPOSIT;
         testing_env!(context.clone());
-        contract.more_sponse_native(String::from("001"), U128(20000));
-        contract.more_sponse_native(String::from("001"), U128(20000));
-        contract.more_sponse_native(String::from("001"), U128(20000));
+        contract.more_sponse_native(String::from("001"), U128(20000), None);
+        contract.more_sponse_native(String::from("001"), U128(20000), None);
+        contract.more_sponse_native(String::from("001"), U128(20000), None);
 
         assert_eq!(
             contract
@@ -274,4 +294,2774 @@ mod tests {
             }
         );
     }
+
+    #[test]
+    fn test_claim_vested() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.accounts.insert(&accounts(2), &0);
+        contract.create_vesting(accounts(2), U128(400), U64(0), U64(100), U64(400), true);
+        assert_eq!(contract.ft_balance_of(accounts(1)), U128(600));
+
+        context.predecessor_account_id = accounts(2);
+        context.block_timestamp = 200;
+        testing_env!(context.clone());
+        assert_eq!(contract.claim_vested(), U128(200));
+        assert_eq!(contract.get_locked_amount(accounts(2)), U128(200));
+
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context);
+        assert_eq!(contract.revoke_vesting(accounts(2)), U128(200));
+        assert_eq!(contract.ft_balance_of(accounts(1)), U128(800));
+        assert_eq!(contract.get_locked_amount(accounts(2)), U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "Nothing has vested since the last claim")]
+    fn test_claim_vested_before_cliff() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.accounts.insert(&accounts(2), &0);
+        contract.create_vesting(accounts(2), U128(400), U64(0), U64(100), U64(400), true);
+
+        context.predecessor_account_id = accounts(2);
+        context.block_timestamp = 50;
+        testing_env!(context);
+        contract.claim_vested();
+    }
+
+    #[test]
+    #[should_panic(expected = "This vesting schedule is not revocable")]
+    fn test_revoke_irrevocable_vesting() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context);
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.accounts.insert(&accounts(2), &0);
+        contract.create_vesting(accounts(2), U128(400), U64(0), U64(100), U64(400), false);
+        contract.revoke_vesting(accounts(2));
+    }
+
+    #[test]
+    fn test_migrate() {
+        use crate::event::{Sponse, Status};
+        use crate::metadata::FungibleTokenMetadata;
+        use crate::migrate::OldContract;
+        use crate::{Prefix, StorageKey};
+        use near_sdk::borsh::BorshSerialize;
+        use near_sdk::collections::{LazyOption, LookupMap, UnorderedSet};
+        use near_sdk::env;
+        use std::collections::{HashMap, HashSet};
+
+        let mut context = get_context();
+        context.predecessor_account_id = accounts(0);
+        testing_env!(context.clone());
+        let metadata = FungibleTokenMetadata {
+            spec: String::from("ft-1.0.0"),
+            name: String::from("Plats Network"),
+            symbol: String::from("PLAT"),
+            icon: None,
+            reference: None,
+            reference_hash: None,
+            decimals: 18,
+        };
+        let mut old = OldContract {
+            accounts: LookupMap::new(StorageKey::Accounts.try_to_vec().unwrap()),
+            total_supply: 1_000,
+            tasks: LookupMap::new(Prefix::SponserToSponse.try_to_vec().unwrap()),
+            bytes_for_longest_account_id: 0,
+            metadata: LazyOption::new(StorageKey::Metadata.try_to_vec().unwrap(), Some(&metadata)),
+            owner_id: accounts(2),
+            events: LookupMap::new(Prefix::Events.try_to_vec().unwrap()),
+            list_event: UnorderedSet::new(Prefix::ListEvent.try_to_vec().unwrap()),
+            client_to_event_id: LookupMap::new(Prefix::ClientToEventId.try_to_vec().unwrap()),
+            sponser_to_sponse: LookupMap::new(Prefix::SponserToSponse.try_to_vec().unwrap()),
+        };
+        old.accounts.insert(&accounts(2), &1_000);
+        let event = Event {
+            id: String::from("001"),
+            owner: accounts(2),
+            name: String::from("Panana"),
+            total_near: 0,
+            total_usdt: 0,
+            status: Status::Active,
+            sponsers: vec![],
+        };
+        old.events.insert(&event.id, &event);
+        old.list_event.insert(&event.id);
+        let sponsored_event = Event {
+            id: String::from("002"),
+            owner: accounts(2),
+            name: String::from("Banana"),
+            total_near: 1_000,
+            total_usdt: 0,
+            status: Status::Active,
+            sponsers: vec![accounts(3)],
+        };
+        old.events.insert(&sponsored_event.id, &sponsored_event);
+        old.list_event.insert(&sponsored_event.id);
+        let amount = Amount {
+            token_near: 1_000,
+            token_usdt: 0,
+        };
+        let sponse = Sponse {
+            events: HashSet::from([sponsored_event.id.clone()]),
+            map_event_amount: HashMap::from([(sponsored_event.id.clone(), amount)]),
+        };
+        old.sponser_to_sponse.insert(&accounts(3), &sponse);
+        env::state_write(&old);
+
+        let mut contract = Contract::migrate();
+        assert_eq!(contract.ft_balance_of(accounts(2)), U128(1_000));
+        assert_eq!(contract.ft_total_supply(), U128(1_000));
+        assert_eq!(contract.watch_detail_event(&String::from("001")), event);
+        assert_eq!(contract.list_event.len(), 2);
+
+        // The aggregates are rebuilt from the events
+        let stats = contract.get_platform_stats();
+        assert_eq!(stats.events_active, 2);
+        assert_eq!(stats.unique_sponsors, 1);
+        assert_eq!(stats.total_near, U128(1_000));
+        assert_eq!(contract.get_owner_stats(accounts(2)).events, 2);
+        assert_eq!(
+            contract.get_largest_events(Token::NEAR, None, None),
+            vec![(sponsored_event.id.clone(), U128(1_000))]
+        );
+        let leaderboard = vec![LeaderboardEntry {
+            sponser_id: accounts(3),
+            value: U128(1_000),
+            near: U128(1_000),
+            usdt: U128(0),
+        }];
+        assert_eq!(
+            contract.get_event_leaderboard(sponsored_event.id.clone(), Ranking::NEAR, None, None),
+            leaderboard
+        );
+        assert_eq!(
+            contract.get_global_leaderboard(Token::NEAR, None, None),
+            leaderboard
+        );
+
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        context.attached_deposit = 1;
+        testing_env!(context);
+        contract.cancel_events(sponsored_event.id);
+        let stats = contract.get_platform_stats();
+        assert_eq!(stats.events_active, 1);
+        assert_eq!(stats.events_cancelled, 1);
+    }
+
+    #[test]
+    fn test_ft_transfer_from() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.accounts.insert(&accounts(3), &0);
+        contract.ft_approve(accounts(2), U128(300));
+
+        context.predecessor_account_id = accounts(2);
+        context.attached_deposit = 1;
+        testing_env!(context);
+        contract.ft_transfer_from(accounts(1), accounts(3), U128(200), None);
+        assert_eq!(contract.ft_balance_of(accounts(3)), U128(200));
+        assert_eq!(contract.ft_allowance(accounts(1), accounts(2)), U128(100));
+    }
+
+    #[test]
+    #[should_panic(expected = "Owner and spender should be different")]
+    fn test_ft_approve_self() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context);
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.ft_approve(accounts(1), U128(300));
+    }
+
+    #[test]
+    fn test_ft_batch_transfer() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        testing_env!(context);
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.accounts.insert(&accounts(2), &0);
+        contract.accounts.insert(&accounts(3), &0);
+
+        let results = contract.ft_batch_transfer(
+            vec![
+                (accounts(2), U128(100)),
+                (accounts(3), U128(200)),
+                (accounts(4), U128(300)),
+            ],
+            None,
+            Some(true),
+        );
+        assert_eq!(results[2].status, BatchTransferStatus::Skipped);
+        assert_eq!(contract.ft_balance_of(accounts(1)), U128(700));
+        assert_eq!(contract.ft_balance_of(accounts(3)), U128(200));
+    }
+
+    #[test]
+    #[should_panic(expected = "Sender and receiver should be different")]
+    fn test_ft_batch_transfer_to_self() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        testing_env!(context);
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.accounts.insert(&accounts(2), &0);
+        contract.ft_batch_transfer(
+            vec![(accounts(2), U128(100)), (accounts(1), U128(300))],
+            None,
+            None,
+        );
+    }
+
+    #[test]
+    fn test_claim_airdrop() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.accounts.insert(&accounts(2), &0);
+
+        // Two leaves: index 0 for accounts(2), index 1 for accounts(3)
+        let leaf_0 = airdrop_leaf_hash(0, &accounts(2), 100);
+        let leaf_1 = airdrop_leaf_hash(1, &accounts(3), 50);
+        let root = env::sha256_array(&[leaf_0, leaf_1].concat());
+        let round_id = contract.create_airdrop(root.into(), U128(150), U64(1_000));
+
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context);
+        contract.claim_airdrop(round_id, 0, U128(100), vec![leaf_1.into()]);
+        assert_eq!(contract.ft_balance_of(accounts(2)), U128(100));
+        assert!(contract.is_airdrop_claimed(round_id, 0));
+        assert!(!contract.is_airdrop_claimed(round_id, 1));
+        assert!(contract.has_claimed_airdrop(round_id, accounts(2)));
+        assert!(!contract.has_claimed_airdrop(round_id, accounts(3)));
+    }
+
+    #[test]
+    #[should_panic(expected = "The airdrop has already been claimed")]
+    fn test_claim_airdrop_twice() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.accounts.insert(&accounts(2), &0);
+        let leaf_0 = airdrop_leaf_hash(0, &accounts(2), 100);
+        let leaf_1 = airdrop_leaf_hash(1, &accounts(3), 50);
+        let root = env::sha256_array(&[leaf_0, leaf_1].concat());
+        let round_id = contract.create_airdrop(root.into(), U128(150), U64(1_000));
+
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context);
+        contract.claim_airdrop(round_id, 0, U128(100), vec![leaf_1.into()]);
+        contract.claim_airdrop(round_id, 0, U128(100), vec![leaf_1.into()]);
+    }
+
+    #[test]
+    fn test_claim_task_reward() {
+        let mut context = get_context();
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.accounts.insert(&accounts(2), &0);
+        contract.tasks.insert(
+            &String::from("task"),
+            &TaskInfo {
+                client: accounts(1),
+                token: Token::PLAT,
+                amount: U128(300),
+                reward: U128(100),
+                max_participants: 10,
+                participants: 0,
+                expires_at: U64(1_000),
+                status: TaskStatus::Active,
+            },
+        );
+        contract.internal_lock(&accounts(1), 300, None);
+
+        let secret = ed25519_dalek::SecretKey::from_bytes(&[7u8; 32]).unwrap();
+        let public = ed25519_dalek::PublicKey::from(&secret);
+        let mut key_bytes = vec![0u8];
+        key_bytes.extend_from_slice(public.as_bytes());
+        contract.set_task_signer(PublicKey::try_from(key_bytes).unwrap());
+
+        let payload = TaskRewardPayload {
+            contract_id: accounts(0),
+            task_id: String::from("task"),
+            receiver_id: accounts(2),
+            amount: 100,
+            nonce: 1,
+            expiry: 1_000,
+        };
+        let signature = ed25519_dalek::ExpandedSecretKey::from(&secret)
+            .sign(&payload.try_to_vec().unwrap(), &public);
+
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context);
+        contract.claim_task_reward(
+            String::from("task"),
+            U128(100),
+            U64(1),
+            U64(1_000),
+            signature.to_bytes().to_vec().into(),
+        );
+        assert_eq!(contract.ft_balance_of(accounts(2)), U128(100));
+        assert_eq!(contract.get_total_deposit(String::from("task")), 200);
+        assert!(contract.is_task_nonce_used(U64(1)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Invalid reward authorization signature")]
+    fn test_claim_task_reward_with_tampered_amount() {
+        let mut context = get_context();
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.accounts.insert(&accounts(2), &0);
+        contract.tasks.insert(
+            &String::from("task"),
+            &TaskInfo {
+                client: accounts(1),
+                token: Token::PLAT,
+                amount: U128(300),
+                reward: U128(100),
+                max_participants: 10,
+                participants: 0,
+                expires_at: U64(1_000),
+                status: TaskStatus::Active,
+            },
+        );
+        contract.internal_lock(&accounts(1), 300, None);
+
+        let secret = ed25519_dalek::SecretKey::from_bytes(&[7u8; 32]).unwrap();
+        let public = ed25519_dalek::PublicKey::from(&secret);
+        let mut key_bytes = vec![0u8];
+        key_bytes.extend_from_slice(public.as_bytes());
+        contract.set_task_signer(PublicKey::try_from(key_bytes).unwrap());
+
+        let payload = TaskRewardPayload {
+            contract_id: accounts(0),
+            task_id: String::from("task"),
+            receiver_id: accounts(2),
+            amount: 100,
+            nonce: 1,
+            expiry: 1_000,
+        };
+        let signature = ed25519_dalek::ExpandedSecretKey::from(&secret)
+            .sign(&payload.try_to_vec().unwrap(), &public);
+
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context);
+        contract.claim_task_reward(
+            String::from("task"),
+            U128(300),
+            U64(1),
+            U64(1_000),
+            signature.to_bytes().to_vec().into(),
+        );
+    }
+
+    #[test]
+    fn test_fund_and_complete_task() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.accounts.insert(&accounts(2), &0);
+        contract.create_task(String::from("task"), U128(100), 2, U64(1_000), None);
+
+        // What `ft_transfer_call` to this contract does before calling `ft_on_transfer`
+        contract.internal_transfer(&accounts(1), &accounts(0), 300, None);
+        context.predecessor_account_id = accounts(0);
+        testing_env!(context.clone());
+        contract.ft_on_transfer(
+            accounts(1),
+            U128(300),
+            String::from("{\"task_id\":\"task\"}"),
+        );
+        assert_eq!(contract.get_total_deposit(String::from("task")), 300);
+
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context);
+        contract.complete_task(String::from("task"), accounts(2));
+        assert_eq!(contract.ft_balance_of(accounts(2)), U128(100));
+        assert!(contract.has_completed_task(String::from("task"), accounts(2)));
+        assert_eq!(
+            contract
+                .get_task(String::from("task"))
+                .unwrap()
+                .participants,
+            1
+        );
+    }
+
+    #[test]
+    fn test_tasks_and_sponsorships_dont_collide() {
+        let mut context = get_context();
+        context.attached_deposit = 5_000 + STORAGE_DEPOSIT;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.create_event(String::from("001"), String::from("Panana"));
+        contract.sponse_native(String::from("001"), U128(5000), None);
+
+        // A task whose id is the account id of the sponsor
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context);
+        contract.create_task(accounts(2).to_string(), U128(100), 2, U64(1_000), None);
+        assert_eq!(contract.get_sponsed().len(), 1);
+        assert_eq!(contract.get_task(accounts(2).to_string()).unwrap().reward, U128(100));
+    }
+
+    #[test]
+    #[should_panic(expected = "The account has already been rewarded for this task")]
+    fn test_complete_task_twice() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.accounts.insert(&accounts(2), &0);
+        contract.create_task(String::from("task"), U128(100), 2, U64(1_000), None);
+        contract.internal_transfer(&accounts(1), &accounts(0), 300, None);
+        context.predecessor_account_id = accounts(0);
+        testing_env!(context.clone());
+        contract.ft_on_transfer(
+            accounts(1),
+            U128(300),
+            String::from("{\"task_id\":\"task\"}"),
+        );
+
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context);
+        contract.complete_task(String::from("task"), accounts(2));
+        contract.complete_task(String::from("task"), accounts(2));
+    }
+
+    #[test]
+    fn test_close_expired_task() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.create_task(String::from("task"), U128(100), 2, U64(1_000), None);
+        contract.internal_transfer(&accounts(1), &accounts(0), 300, None);
+        contract.internal_fund_task(&accounts(1), &String::from("task"), Token::PLAT, 300);
+
+        context.block_timestamp = 1_000;
+        testing_env!(context);
+        assert_eq!(contract.get_expired_tasks_with_budget(None, None).len(), 1);
+        assert_eq!(contract.close_task(String::from("task")), U128(300));
+        assert_eq!(contract.ft_balance_of(accounts(1)), U128(1_000));
+        assert_eq!(
+            contract.get_task(String::from("task")).unwrap().status,
+            TaskStatus::Closed
+        );
+        assert!(contract
+            .get_expired_tasks_with_budget(None, None)
+            .is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "The task has not expired yet")]
+    fn test_close_task_before_expiry() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.create_task(String::from("task"), U128(100), 2, U64(1_000), None);
+
+        context.block_timestamp = 999;
+        testing_env!(context);
+        contract.close_task(String::from("task"));
+    }
+
+    #[test]
+    fn test_complete_quest() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.accounts.insert(&accounts(2), &0);
+        contract
+            .task_participants
+            .insert(&(String::from("follow"), accounts(2)));
+        let quest_id = contract.create_quest(
+            String::from("Onboarding"),
+            vec![
+                QuestStep::Task {
+                    task_id: String::from("follow"),
+                },
+                QuestStep::HoldPlat { amount: U128(50) },
+            ],
+            true,
+            U128(100),
+            2,
+        );
+        contract.internal_transfer(&accounts(1), &accounts(2), 50, None);
+
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context);
+        contract.verify_quest_step(quest_id, 0);
+        contract.verify_quest_step(quest_id, 1);
+        assert_eq!(contract.claim_quest_bonus(quest_id), U128(100));
+        assert_eq!(contract.ft_balance_of(accounts(2)), U128(150));
+        assert_eq!(contract.get_quest(quest_id).unwrap().budget, U128(100));
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The account no longer holds the PLAT or the sponsorships the quest asks for"
+    )]
+    fn test_claim_quest_bonus_after_selling() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.accounts.insert(&accounts(2), &0);
+        let quest_id = contract.create_quest(
+            String::from("Holder"),
+            vec![QuestStep::HoldPlat { amount: U128(50) }],
+            false,
+            U128(100),
+            2,
+        );
+        contract.internal_transfer(&accounts(1), &accounts(2), 50, None);
+
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context);
+        contract.verify_quest_step(quest_id, 0);
+        contract.internal_transfer(&accounts(2), &accounts(1), 50, None);
+        contract.claim_quest_bonus(quest_id);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "The account no longer holds the PLAT or the sponsorships the quest asks for"
+    )]
+    fn test_claim_quest_bonus_after_refund() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.create_event(String::from("001"), String::from("Panana"));
+        let quest_id = contract.create_quest(
+            String::from("Sponsor"),
+            vec![QuestStep::SponsorEvent {
+                event_id: String::from("001"),
+            }],
+            false,
+            U128(100),
+            2,
+        );
+
+        context.attached_deposit = 5_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(String::from("001"), U128(5000), None);
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        contract.verify_quest_step(quest_id, 0);
+
+        context.attached_deposit = 1;
+        testing_env!(context);
+        contract.cancel_events(String::from("001"));
+        contract.claim(&String::from("001"));
+        contract.claim_quest_bonus(quest_id);
+    }
+
+    #[test]
+    fn test_award_and_redeem_points() {
+        let mut context = get_context();
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.accounts.insert(&accounts(2), &0);
+        contract.add_point_operator(accounts(1));
+        contract.award_points(accounts(2), U128(30), Some(String::from("Joined")));
+        contract.award_points(accounts(3), U128(50), None);
+        contract.set_points_rate(U128(2));
+        contract.fund_points_pool(U128(100));
+
+        let leaderboard = contract.get_points_leaderboard(None, None);
+        assert_eq!(leaderboard[0], (accounts(3), U128(50)));
+        assert_eq!(leaderboard[1], (accounts(2), U128(30)));
+
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context.clone());
+        assert_eq!(contract.redeem_points(U128(20)), U128(40));
+        assert_eq!(contract.ft_balance_of(accounts(2)), U128(40));
+        assert_eq!(contract.get_points(accounts(2), None), U128(10));
+
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context);
+        contract.start_points_season();
+        assert_eq!(contract.get_points(accounts(2), None), U128(0));
+        assert_eq!(contract.get_points(accounts(2), Some(0)), U128(10));
+        assert!(contract.get_points_leaderboard(None, None).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "Only a point operator can award points")]
+    fn test_award_points_without_operator_role() {
+        let mut context = get_context();
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.add_point_operator(accounts(1));
+
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context);
+        contract.award_points(accounts(2), U128(30), None);
+    }
+
+    #[test]
+    fn test_draw_raffle() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        for account in [accounts(2), accounts(3), accounts(4)] {
+            contract.accounts.insert(&account, &0);
+            contract
+                .task_participants
+                .insert(&(String::from("task"), account));
+        }
+        let secret = b"operator secret".to_vec();
+        let raffle_id = contract.create_raffle(
+            String::from("Giveaway"),
+            RaffleEligibility::Task {
+                task_id: String::from("task"),
+            },
+            Token::PLAT,
+            U128(100),
+            2,
+            env::sha256_array(&secret).into(),
+        );
+
+        for account in [accounts(2), accounts(3), accounts(4)] {
+            context.predecessor_account_id = account;
+            testing_env!(context.clone());
+            contract.join_raffle(raffle_id);
+        }
+
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context);
+        contract.close_raffle_registration(raffle_id);
+        let winners = contract.draw_raffle(raffle_id, secret.into());
+        assert_eq!(winners.len(), 2);
+        assert_ne!(winners[0], winners[1]);
+        for winner in winners {
+            assert_eq!(contract.ft_balance_of(winner), U128(100));
+        }
+        assert_eq!(contract.ft_balance_of(accounts(1)), U128(800));
+    }
+
+    #[test]
+    fn test_cancel_raffle() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let secret = b"operator secret".to_vec();
+        let raffle_id = contract.create_raffle(
+            String::from("Giveaway"),
+            RaffleEligibility::Task {
+                task_id: String::from("task"),
+            },
+            Token::PLAT,
+            U128(100),
+            2,
+            env::sha256_array(&secret).into(),
+        );
+        assert_eq!(contract.ft_balance_of(accounts(1)), U128(800));
+
+        // The operator never draws: anyone can cancel once the draw timeout has passed
+        context.predecessor_account_id = accounts(3);
+        context.block_timestamp = crate::raffle::RAFFLE_DRAW_TIMEOUT;
+        testing_env!(context);
+        assert_eq!(contract.cancel_raffle(raffle_id), U128(200));
+        assert_eq!(contract.ft_balance_of(accounts(1)), U128(1_000));
+        assert_eq!(
+            contract.get_raffle(raffle_id).unwrap().status,
+            crate::raffle::RaffleStatus::Cancelled
+        );
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Only the operator or the owner can cancel the raffle before its draw timeout"
+    )]
+    fn test_cancel_raffle_before_timeout() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let raffle_id = contract.create_raffle(
+            String::from("Giveaway"),
+            RaffleEligibility::Task {
+                task_id: String::from("task"),
+            },
+            Token::PLAT,
+            U128(100),
+            2,
+            env::sha256_array(b"operator secret").into(),
+        );
+
+        context.predecessor_account_id = accounts(3);
+        testing_env!(context);
+        contract.cancel_raffle(raffle_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "A raffle can't have more than 10 winners")]
+    fn test_create_raffle_too_many_winners() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context);
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.create_raffle(
+            String::from("Giveaway"),
+            RaffleEligibility::Task {
+                task_id: String::from("task"),
+            },
+            Token::PLAT,
+            U128(1),
+            11,
+            env::sha256_array(b"operator secret").into(),
+        );
+    }
+
+    #[test]
+    fn test_draw_raffle_skips_refunded_sponsor() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.create_event(String::from("001"), String::from("Panana"));
+        let secret = b"operator secret".to_vec();
+        let raffle_id = contract.create_raffle(
+            String::from("Sponsors"),
+            RaffleEligibility::Event {
+                event_id: String::from("001"),
+            },
+            Token::PLAT,
+            U128(100),
+            2,
+            env::sha256_array(&secret).into(),
+        );
+
+        for account in [accounts(2), accounts(3)] {
+            contract.accounts.insert(&account, &0);
+            context.signer_account_id = account.clone();
+            context.predecessor_account_id = account;
+            context.attached_deposit = 5_000 + STORAGE_DEPOSIT;
+            testing_env!(context.clone());
+            contract.sponse_native(String::from("001"), U128(5000), None);
+            context.attached_deposit = 10u128.pow(24);
+            testing_env!(context.clone());
+            contract.join_raffle(raffle_id);
+        }
+
+        // accounts(3) takes its sponsorship back before the draw
+        context.signer_account_id = accounts(1);
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        contract.cancel_events(String::from("001"));
+        context.signer_account_id = accounts(3);
+        testing_env!(context.clone());
+        contract.claim(&String::from("001"));
+
+        context.signer_account_id = accounts(1);
+        testing_env!(context);
+        contract.close_raffle_registration(raffle_id);
+        let winners = contract.draw_raffle(raffle_id, secret.into());
+        assert_eq!(winners, vec![accounts(2)]);
+        assert_eq!(contract.ft_balance_of(accounts(2)), U128(100));
+        assert_eq!(contract.ft_balance_of(accounts(3)), U128(0));
+        assert_eq!(contract.ft_balance_of(accounts(1)), U128(900));
+    }
+
+    #[test]
+    fn test_retry_raffle_payout() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract
+            .task_participants
+            .insert(&(String::from("task"), accounts(2)));
+        let secret = b"operator secret".to_vec();
+        let raffle_id = contract.create_raffle(
+            String::from("Giveaway"),
+            RaffleEligibility::Task {
+                task_id: String::from("task"),
+            },
+            Token::NEAR,
+            U128(100),
+            1,
+            env::sha256_array(&secret).into(),
+        );
+        context.attached_deposit = 100;
+        testing_env!(context.clone());
+        contract.fund_raffle_native(raffle_id);
+
+        context.predecessor_account_id = accounts(2);
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        contract.join_raffle(raffle_id);
+
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        contract.close_raffle_registration(raffle_id);
+        assert_eq!(
+            contract.draw_raffle(raffle_id, secret.into()),
+            vec![accounts(2)]
+        );
+
+        testing_env!(
+            context.clone(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        contract.raffle_payout_callback(raffle_id, accounts(2), U128(100));
+        assert_eq!(
+            contract.get_raffle(raffle_id).unwrap().unpaid,
+            vec![(accounts(2), U128(100))]
+        );
+
+        testing_env!(context);
+        contract.retry_raffle_payout(raffle_id, accounts(2));
+        assert!(contract.get_raffle(raffle_id).unwrap().unpaid.is_empty());
+    }
+
+    #[test]
+    fn test_distribute_prizes() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        for sponser in [accounts(3), accounts(4)] {
+            context.signer_account_id = sponser;
+            testing_env!(context.clone());
+            contract.sponse_native(event_id.clone(), U128(1_000), None);
+        }
+
+        // accounts(2) created the contract, so it is also its owner
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        contract.finish_event(event_id.clone());
+
+        context.signer_account_id = accounts(2);
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let distribution = contract.submit_prize_winners(
+            event_id.clone(),
+            vec![
+                PrizeWinner {
+                    account_id: accounts(3),
+                    token: Token::NEAR,
+                    share: PrizeShare::Fixed { amount: U128(500) },
+                },
+                PrizeWinner {
+                    account_id: accounts(4),
+                    token: Token::NEAR,
+                    share: PrizeShare::Bps { bps: 5_000 },
+                },
+            ],
+        );
+        assert_eq!(distribution.total_near, U128(1_500));
+        assert_eq!(contract.watch_detail_event(&event_id).total_near, 500);
+        assert_eq!(contract.get_platform_stats().total_near, U128(500));
+        assert_eq!(contract.pay_prizes(event_id.clone(), None), 2);
+
+        testing_env!(
+            context.clone(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        contract.prize_payout_callback(event_id.clone(), 1);
+        let payouts = contract.get_prize_payouts(event_id.clone(), None, None);
+        assert_eq!(payouts[0].status, PayoutStatus::InFlight);
+        assert_eq!(payouts[1].status, PayoutStatus::Failed);
+        assert_eq!(payouts[1].amount, U128(1_000));
+
+        contract.retry_prize_payout(event_id.clone(), 1);
+        testing_env!(
+            context,
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])],
+        );
+        contract.prize_payout_callback(event_id.clone(), 1);
+        let distribution = contract.get_prize_distribution(event_id).unwrap();
+        assert_eq!(distribution.paid_near, U128(1_000));
+    }
+
+    #[test]
+    #[should_panic(expected = "The NEAR prizes exceed the NEAR sponsored to the event")]
+    fn test_prizes_above_the_prize_pool() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+
+        context.signer_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(1_000), None);
+
+        context.signer_account_id = accounts(2);
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context);
+        contract.finish_event(event_id.clone());
+        contract.submit_prize_winners(
+            event_id,
+            vec![PrizeWinner {
+                account_id: accounts(3),
+                token: Token::NEAR,
+                share: PrizeShare::Fixed {
+                    amount: U128(1_001),
+                },
+            }],
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Only an active event can be cancelled")]
+    fn test_cancel_finished_event() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context);
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.finish_event(event_id.clone());
+        contract.cancel_events(event_id);
+    }
+
+    #[test]
+    fn test_milestone_votes() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        contract.set_event_milestones(
+            event_id.clone(),
+            vec![
+                (String::from("Venue"), 4_000),
+                (String::from("Show"), 6_000),
+            ],
+            5_000,
+            U64(1_000),
+        );
+
+        for (sponser, amount) in [(accounts(3), 3_000), (accounts(4), 1_000)] {
+            context.signer_account_id = sponser.clone();
+            context.predecessor_account_id = sponser;
+            context.attached_deposit = amount + STORAGE_DEPOSIT;
+            testing_env!(context.clone());
+            contract.sponse_native(event_id.clone(), U128(amount), None);
+        }
+
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        contract.request_milestone_release(event_id.clone());
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        testing_env!(context.clone());
+        assert_eq!(
+            contract.vote_milestone(event_id.clone(), true),
+            MilestoneStatus::Released
+        );
+        let escrow = contract.get_milestone_escrow(event_id.clone()).unwrap();
+        assert_eq!(escrow.released_bps, 4_000);
+        assert_eq!(escrow.next_milestone, 1);
+
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context.clone());
+        contract.request_milestone_release(event_id.clone());
+
+        for (sponser, status) in [
+            (accounts(4), MilestoneStatus::Voting),
+            (accounts(3), MilestoneStatus::Rejected),
+        ] {
+            context.signer_account_id = sponser.clone();
+            context.predecessor_account_id = sponser;
+            testing_env!(context.clone());
+            assert_eq!(contract.vote_milestone(event_id.clone(), false), status);
+        }
+        assert_eq!(
+            contract.watch_detail_event(&event_id).status,
+            crate::event::Status::Cancel
+        );
+        assert_eq!(contract.internal_refundable_amount(&event_id, 1_000), 600);
+    }
+
+    #[test]
+    #[should_panic(expected = "The event is not active")]
+    fn test_vote_milestone_of_cancelled_event() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        contract.set_event_milestones(
+            event_id.clone(),
+            vec![(String::from("Venue"), 10_000)],
+            5_000,
+            U64(1_000),
+        );
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(1_000), None);
+
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        contract.request_milestone_release(event_id.clone());
+        contract.cancel_events(event_id.clone());
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        testing_env!(context);
+        contract.vote_milestone(event_id, true);
+    }
+
+    #[test]
+    #[should_panic(expected = "The event is not active")]
+    fn test_finalize_milestone_vote_of_finished_event() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        contract.set_event_milestones(
+            event_id.clone(),
+            vec![(String::from("Venue"), 10_000)],
+            5_000,
+            U64(1_000),
+        );
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(1_000), None);
+
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        contract.request_milestone_release(event_id.clone());
+        contract.finish_event(event_id.clone());
+
+        // A rejection now would cancel the finished event and reopen its refunds
+        context.block_timestamp = 1_001;
+        testing_env!(context);
+        contract.finalize_milestone_vote(event_id);
+    }
+
+    #[test]
+    fn test_cancellation_vote() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+
+        for (sponser, amount) in [(accounts(3), 3_000), (accounts(4), 1_000)] {
+            context.signer_account_id = sponser.clone();
+            context.predecessor_account_id = sponser;
+            context.attached_deposit = amount + STORAGE_DEPOSIT;
+            testing_env!(context.clone());
+            contract.sponse_native(event_id.clone(), U128(amount), None);
+        }
+
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        contract.open_cancellation_vote(event_id.clone());
+        assert!(!contract.vote_cancellation(event_id.clone()).passed);
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        testing_env!(context);
+        assert!(contract.vote_cancellation(event_id.clone()).passed);
+        assert_eq!(
+            contract.watch_detail_event(&event_id).status,
+            crate::event::Status::Cancel
+        );
+    }
+
+    #[test]
+    fn test_rule_dispute() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.set_event_arbitrator(event_id.clone(), Some(accounts(5)));
+
+        context.signer_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(1_000), None);
+
+        context.signer_account_id = accounts(5);
+        context.predecessor_account_id = accounts(5);
+        testing_env!(context);
+        contract.rule_dispute(event_id.clone(), Ruling::Split { owner_bps: 3_000 });
+        assert_eq!(
+            contract.watch_detail_event(&event_id).status,
+            crate::event::Status::Cancel
+        );
+        assert_eq!(contract.internal_refundable_amount(&event_id, 1_000), 700);
+    }
+
+    #[test]
+    #[should_panic(expected = "Only the arbitrator of the event can rule on it")]
+    fn test_rule_dispute_without_being_arbitrator() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.set_event_arbitrator(event_id.clone(), Some(accounts(5)));
+
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context);
+        contract.rule_dispute(event_id, Ruling::Release);
+    }
+
+    #[test]
+    fn test_withdraw_unpaid_ruling_funds() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.set_event_arbitrator(event_id.clone(), Some(accounts(5)));
+
+        context.signer_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(1_000), None);
+
+        context.signer_account_id = accounts(5);
+        context.predecessor_account_id = accounts(5);
+        testing_env!(context.clone());
+        contract.rule_dispute(event_id.clone(), Ruling::Split { owner_bps: 3_000 });
+
+        // The transfer of the owner's share fails
+        context.predecessor_account_id = context.current_account_id.clone();
+        testing_env!(
+            context.clone(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        contract.ruling_payout_callback(event_id.clone(), Token::NEAR, U128(300));
+        assert_eq!(
+            contract.get_unpaid_ruling_funds(event_id.clone()),
+            Some(Amount {
+                token_near: 300,
+                token_usdt: 0
+            })
+        );
+
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context);
+        contract.withdraw_unpaid_ruling_funds(event_id.clone());
+        assert_eq!(contract.get_unpaid_ruling_funds(event_id), None);
+    }
+
+    #[test]
+    fn test_withdraw_sponsorship() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.set_withdrawal_policy(
+            event_id.clone(),
+            Some(WithdrawalPolicy {
+                window: U64(1_000),
+                penalty_bps: 1_000,
+                penalty_to: PenaltyReceiver::Event,
+            }),
+        );
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(1_000), None);
+
+        context.attached_deposit = STORAGE_DEPOSIT;
+        testing_env!(context);
+        let payout = contract.withdraw_sponsorship(event_id.clone(), Token::NEAR, U128(500));
+        assert_eq!(payout, U128(450));
+        assert_eq!(contract.get_total_token_event(&event_id).token_near, 550);
+        assert_eq!(
+            contract
+                .internal_unwrap_balance(&accounts(3), &event_id)
+                .unwrap()
+                .token_near,
+            500
+        );
+
+        contract.withdraw_sponsorship(event_id.clone(), Token::NEAR, U128(500));
+        assert_eq!(contract.get_total_token_event(&event_id).token_near, 100);
+        assert!(contract.get_all_sponser_event(event_id.clone()).is_empty());
+        assert!(contract
+            .get_sponsorship_receipt(event_id, accounts(3))
+            .is_none());
+    }
+
+    #[test]
+    fn test_withdraw_sponsorship_failed_payout() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.set_withdrawal_policy(
+            event_id.clone(),
+            Some(WithdrawalPolicy {
+                window: U64(1_000),
+                penalty_bps: 1_000,
+                penalty_to: PenaltyReceiver::Event,
+            }),
+        );
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(1_000), None);
+
+        context.attached_deposit = STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.withdraw_sponsorship(event_id.clone(), Token::NEAR, U128(1_000));
+        assert_eq!(contract.get_withdrawal_penalties(event_id.clone()).token_near, 100);
+
+        // The payout fails, so the whole withdrawal is undone
+        context.predecessor_account_id = context.current_account_id.clone();
+        testing_env!(
+            context.clone(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        contract.withdraw_sponsorship_callback(
+            event_id.clone(),
+            accounts(3),
+            Token::NEAR,
+            U128(900),
+            U128(100),
+            U64(0),
+        );
+        assert_eq!(contract.get_withdrawal_penalties(event_id.clone()).token_near, 0);
+        assert_eq!(contract.get_total_token_event(&event_id).token_near, 1_000);
+        assert_eq!(
+            contract
+                .internal_unwrap_balance(&accounts(3), &event_id)
+                .unwrap()
+                .token_near,
+            1_000
+        );
+
+        // The sponsor is still within their withdrawal window
+        context.predecessor_account_id = accounts(3);
+        testing_env!(context);
+        let payout = contract.withdraw_sponsorship(event_id, Token::NEAR, U128(500));
+        assert_eq!(payout, U128(450));
+    }
+
+    #[test]
+    #[should_panic(expected = "The withdrawal window is over")]
+    fn test_withdraw_sponsorship_after_window() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.set_withdrawal_policy(
+            event_id.clone(),
+            Some(WithdrawalPolicy {
+                window: U64(1_000),
+                penalty_bps: 1_000,
+                penalty_to: PenaltyReceiver::Event,
+            }),
+        );
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(1_000), None);
+
+        context.attached_deposit = STORAGE_DEPOSIT;
+        context.block_timestamp = 1_001;
+        testing_env!(context);
+        contract.withdraw_sponsorship(event_id, Token::NEAR, U128(500));
+    }
+
+    #[test]
+    fn test_sweep_unclaimed() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.set_claim_deadline(event_id.clone(), Some(U64(1_000)));
+
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        for sponser in [accounts(3), accounts(4)] {
+            context.signer_account_id = sponser;
+            testing_env!(context.clone());
+            contract.sponse_native(event_id.clone(), U128(1_000), None);
+        }
+
+        context.signer_account_id = accounts(2);
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        contract.cancel_events(event_id.clone());
+
+        // accounts(2) owns the contract and is its default treasurer
+        context.predecessor_account_id = accounts(2);
+        context.block_timestamp = 2_000;
+        testing_env!(context);
+        assert_eq!(
+            contract.sweep_unclaimed(event_id.clone(), None),
+            (U128(2_000), U128(0))
+        );
+        assert!(contract.get_all_sponser_event(event_id.clone()).is_empty());
+        assert_eq!(contract.get_total_token_event(&event_id).token_near, 0);
+        assert!(contract
+            .get_sponsorship_receipt(event_id, accounts(3))
+            .is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "The claim deadline is not over yet")]
+    fn test_sweep_unclaimed_before_deadline() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.set_claim_deadline(event_id.clone(), Some(U64(1_000)));
+
+        context.signer_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(1_000), None);
+
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        context.attached_deposit = 1;
+        context.block_timestamp = 1_000;
+        testing_env!(context);
+        contract.cancel_events(event_id.clone());
+        contract.sweep_unclaimed(event_id, None);
+    }
+
+    #[test]
+    fn test_platform_fee() {
+        let mut context = get_context();
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.set_platform_fee(1_000);
+        contract.set_token_fee(Token::USDT, Some(500));
+        assert_eq!(contract.get_platform_fee(Some(Token::NEAR)), 1_000);
+        assert_eq!(contract.get_platform_fee(Some(Token::USDT)), 500);
+
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+
+        context.signer_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(1_000), None);
+        assert_eq!(contract.get_total_token_event(&event_id).token_near, 900);
+        assert_eq!(contract.get_event_fees(event_id.clone()).near, U128(100));
+        assert_eq!(contract.get_total_fees(Token::NEAR), U128(100));
+
+        context.signer_account_id = accounts(1);
+        testing_env!(context);
+        contract.finish_event(event_id.clone());
+        contract.settle_event_fees(event_id);
+        assert_eq!(contract.get_treasury_balance(Token::NEAR), U128(100));
+        contract.withdraw_fees(Token::NEAR, U128(100));
+        assert_eq!(contract.get_treasury_balance(Token::NEAR), U128(0));
+    }
+
+    #[test]
+    fn test_fee_refund_policy_of_cancelled_events() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.set_platform_fee(1_000);
+
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context.clone());
+        contract.create_event(String::from("001"), String::from("Panana"));
+        contract.create_event(String::from("002"), String::from("AHAHA"));
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(String::from("001"), U128(1_000), None);
+        contract.sponse_native(String::from("002"), U128(1_000), None);
+
+        // 001 is cancelled and its fees are settled while fees are not refunded
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        contract.cancel_events(String::from("001"));
+        contract.settle_event_fees(String::from("001"));
+        assert_eq!(contract.get_treasury_balance(Token::NEAR), U128(100));
+
+        context.signer_account_id = accounts(1);
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context.clone());
+        contract.set_refund_fees_on_cancel(true);
+
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context.clone());
+        contract.cancel_events(String::from("002"));
+        assert!(!contract.are_event_fees_refunded(String::from("001")));
+        assert!(contract.are_event_fees_refunded(String::from("002")));
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        testing_env!(context);
+        contract.claim(&String::from("001"));
+        assert_eq!(contract.get_event_fees(String::from("001")).near, U128(100));
+        contract.claim(&String::from("002"));
+        assert_eq!(contract.get_event_fees(String::from("002")).near, U128(0));
+        assert_eq!(contract.get_treasury_balance(Token::NEAR), U128(100));
+    }
+
+    #[test]
+    #[should_panic(expected = "The fees of the event can still be refunded")]
+    fn test_settle_refunded_fees() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.set_platform_fee(1_000);
+        contract.set_refund_fees_on_cancel(true);
+
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context.clone());
+        contract.create_event(String::from("001"), String::from("Panana"));
+
+        context.signer_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(String::from("001"), U128(1_000), None);
+
+        context.signer_account_id = accounts(2);
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        contract.cancel_events(String::from("001"));
+
+        // Turning the refunds off doesn't change the policy of the cancelled event
+        context.predecessor_account_id = accounts(1);
+        testing_env!(context);
+        contract.set_refund_fees_on_cancel(false);
+        contract.settle_event_fees(String::from("001"));
+    }
+
+    #[test]
+    fn test_withdrawal_penalties() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        for (event_id, penalty_to) in [
+            (String::from("001"), PenaltyReceiver::Event),
+            (String::from("002"), PenaltyReceiver::Treasury),
+        ] {
+            contract.create_event(event_id.clone(), String::from("Panana"));
+            contract.set_withdrawal_policy(
+                event_id,
+                Some(WithdrawalPolicy {
+                    window: U64(1_000),
+                    penalty_bps: 1_000,
+                    penalty_to,
+                }),
+            );
+        }
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(String::from("001"), U128(1_000), None);
+        contract.sponse_native(String::from("002"), U128(1_000), None);
+
+        context.attached_deposit = STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.withdraw_sponsorship(String::from("001"), Token::NEAR, U128(500));
+        assert_eq!(
+            contract
+                .get_withdrawal_penalties(String::from("001"))
+                .token_near,
+            50
+        );
+        contract.withdraw_sponsorship(String::from("002"), Token::NEAR, U128(500));
+        assert_eq!(contract.get_treasury_balance(Token::NEAR), U128(0));
+
+        // The treasury gets its penalty once the payout is received
+        context.predecessor_account_id = context.current_account_id.clone();
+        testing_env!(
+            context.clone(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(vec![])],
+        );
+        contract.withdraw_sponsorship_callback(
+            String::from("002"),
+            accounts(3),
+            Token::NEAR,
+            U128(450),
+            U128(50),
+            U64(0),
+        );
+        assert_eq!(contract.get_treasury_balance(Token::NEAR), U128(50));
+
+        // Nobody can claim the penalty back, so it goes to the treasury
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context);
+        contract.cancel_events(String::from("001"));
+        assert_eq!(contract.get_treasury_balance(Token::NEAR), U128(100));
+        assert_eq!(
+            contract.get_largest_events(Token::NEAR, None, None),
+            vec![
+                (String::from("002"), U128(500)),
+                (String::from("001"), U128(500))
+            ]
+        );
+        assert_eq!(
+            contract
+                .get_total_token_event(&String::from("001"))
+                .token_near,
+            500
+        );
+        assert_eq!(
+            contract
+                .get_withdrawal_penalties(String::from("001"))
+                .token_near,
+            0
+        );
+    }
+
+    #[test]
+    fn test_sweep_unclaimed_refunded_fees() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.set_platform_fee(1_000);
+        contract.set_refund_fees_on_cancel(true);
+
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context.clone());
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.set_claim_deadline(event_id.clone(), Some(U64(1_000)));
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(1_000), None);
+
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        contract.cancel_events(event_id.clone());
+
+        // The fees can no longer be refunded once the refund is swept
+        context.signer_account_id = accounts(1);
+        context.predecessor_account_id = accounts(1);
+        context.block_timestamp = 2_000;
+        testing_env!(context.clone());
+        assert_eq!(
+            contract.sweep_unclaimed(event_id.clone(), None),
+            (U128(900), U128(0))
+        );
+        assert_eq!(
+            contract.get_event_fees(event_id.clone()).settled_near,
+            U128(100)
+        );
+        assert_eq!(contract.get_treasury_balance(Token::NEAR), U128(100));
+
+        // A failed transfer leaves the swept refunds in the treasury balance
+        context.predecessor_account_id = context.current_account_id.clone();
+        testing_env!(
+            context,
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Failed],
+        );
+        contract.sweep_unclaimed_callback(Token::NEAR, U128(900));
+        assert_eq!(contract.get_treasury_balance(Token::NEAR), U128(1_000));
+    }
+
+    #[test]
+    fn test_sponsor_tiers() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        let tier = |name: &str, min_contribution| SponsorTier {
+            name: String::from(name),
+            min_contribution: U128(min_contribution),
+            perks_uri: format!("ipfs://{}", name),
+        };
+        contract.set_sponsor_tiers(
+            event_id.clone(),
+            Token::NEAR,
+            U128(1),
+            U128(1),
+            vec![tier("Silver", 1_000), tier("Gold", 3_000)],
+        );
+
+        context.signer_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(1_000), None);
+        assert_eq!(
+            contract.get_sponsor_tier(event_id.clone(), accounts(3)),
+            Some(tier("Silver", 1_000))
+        );
+
+        context.attached_deposit = 2_000 + STORAGE_DEPOSIT;
+        testing_env!(context);
+        contract.more_sponse_native(event_id.clone(), U128(2_000), None);
+        assert_eq!(
+            contract.get_sponsor_tier(event_id, accounts(3)),
+            Some(tier("Gold", 3_000))
+        );
+        assert!(get_logs()
+            .iter()
+            .any(|log| log.contains("sponsor_tier_change")));
+    }
+
+    #[test]
+    #[should_panic(expected = "The reference token should be NEAR or USDT")]
+    fn test_sponsor_tiers_in_plat() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        context.signer_account_id = accounts(2);
+        testing_env!(context);
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.set_sponsor_tiers(
+            event_id,
+            Token::PLAT,
+            U128(1),
+            U128(1),
+            vec![SponsorTier {
+                name: String::from("Gold"),
+                min_contribution: U128(1_000),
+                perks_uri: String::from("ipfs://gold"),
+            }],
+        );
+    }
+
+    #[test]
+    fn test_sponsor_tier_after_withdrawal() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.set_sponsor_tiers(
+            event_id.clone(),
+            Token::NEAR,
+            U128(1),
+            U128(1),
+            vec![SponsorTier {
+                name: String::from("Gold"),
+                min_contribution: U128(3_000),
+                perks_uri: String::from("ipfs://gold"),
+            }],
+        );
+        contract.set_withdrawal_policy(
+            event_id.clone(),
+            Some(WithdrawalPolicy {
+                window: U64(1_000),
+                penalty_bps: 0,
+                penalty_to: PenaltyReceiver::Event,
+            }),
+        );
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 3_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(3_000), None);
+
+        context.attached_deposit = STORAGE_DEPOSIT;
+        testing_env!(context);
+        contract.withdraw_sponsorship(event_id.clone(), Token::NEAR, U128(2_500));
+        assert_eq!(contract.get_sponsor_tier(event_id, accounts(3)), None);
+        assert!(get_logs()
+            .iter()
+            .any(|log| log.contains("sponsor_tier_change")
+                && log.contains(r#""old_tier":"Gold""#)
+                && !log.contains("new_tier")));
+    }
+
+    #[test]
+    fn test_sponsorship_receipts() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.set_sponsor_tiers(
+            event_id.clone(),
+            Token::NEAR,
+            U128(1),
+            U128(1),
+            vec![SponsorTier {
+                name: String::from("Gold"),
+                min_contribution: U128(1_000),
+                perks_uri: String::from("ipfs://gold"),
+            }],
+        );
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(1_000), None);
+        let receipt = contract
+            .get_sponsorship_receipt(event_id.clone(), accounts(3))
+            .unwrap();
+        assert_eq!(receipt.owner_id, accounts(3));
+        assert_eq!(
+            receipt.metadata.title.as_deref(),
+            Some("Panana - Gold sponsor")
+        );
+        assert_eq!(receipt.metadata.reference.as_deref(), Some("ipfs://gold"));
+        assert_eq!(contract.nft_total_supply(), U128(1));
+        assert!(get_logs().iter().any(|log| log.contains("nft_mint")));
+
+        // receipts are soulbound until the owner of the event allows transfers
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        contract.set_receipts_transferable(event_id.clone(), true);
+        context.signer_account_id = accounts(3);
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        contract.nft_transfer(accounts(4), receipt.token_id.clone(), None, None);
+        assert_eq!(contract.nft_supply_for_owner(accounts(3)), U128(0));
+        assert_eq!(
+            contract.nft_tokens_for_owner(accounts(4), None, None)[0].token_id,
+            receipt.token_id
+        );
+
+        // a full refund burns the receipt, whoever holds it
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        contract.cancel_events(event_id.clone());
+        context.signer_account_id = accounts(3);
+        testing_env!(context);
+        contract.claim(&event_id);
+        assert_eq!(contract.nft_token(receipt.token_id), None);
+        assert_eq!(contract.nft_supply_for_owner(accounts(4)), U128(0));
+        assert_eq!(contract.nft_total_supply(), U128(0));
+    }
+
+    #[test]
+    #[should_panic(expected = "The receipts of this event are soulbound")]
+    fn test_transfer_soulbound_receipt() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(1_000), None);
+        let receipt = contract
+            .get_sponsorship_receipt(event_id, accounts(3))
+            .unwrap();
+
+        context.attached_deposit = 1;
+        testing_env!(context);
+        contract.nft_transfer(accounts(4), receipt.token_id, None, None);
+    }
+
+    #[test]
+    fn test_transfer_sponsorship() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.internal_register_account(&accounts(4));
+
+        for (sponser_id, amount) in [(accounts(3), 1_000), (accounts(4), 500)] {
+            context.signer_account_id = sponser_id.clone();
+            context.predecessor_account_id = sponser_id;
+            context.attached_deposit = amount + STORAGE_DEPOSIT;
+            testing_env!(context.clone());
+            contract.sponse_native(event_id.clone(), U128(amount), None);
+        }
+
+        // accounts(4) already sponsors the event, so the stake can only be merged
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context);
+        contract.transfer_sponsorship(event_id.clone(), accounts(4), Some(true));
+
+        assert_eq!(
+            contract.internal_unwrap_balance(&accounts(4), &event_id),
+            Ok(Amount {
+                token_near: 1_500,
+                token_usdt: 0
+            })
+        );
+        assert!(contract
+            .internal_unwrap_balance(&accounts(3), &event_id)
+            .is_err());
+        assert_eq!(
+            contract.get_all_sponser_event(event_id.clone()),
+            vec![accounts(4)]
+        );
+        assert_eq!(contract.get_total_token_event(&event_id).token_near, 1_500);
+        assert!(contract
+            .get_sponsorship_receipt(event_id.clone(), accounts(3))
+            .is_none());
+        assert!(contract
+            .get_sponsorship_receipt(event_id, accounts(4))
+            .is_some());
+        assert!(get_logs()
+            .iter()
+            .any(|log| log.contains("sponsorship_transfer")));
+    }
+
+    #[test]
+    #[should_panic(expected = "The receiver already sponsors this event")]
+    fn test_transfer_sponsorship_without_merge() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.internal_register_account(&accounts(4));
+
+        for (sponser_id, amount) in [(accounts(3), 1_000), (accounts(4), 500)] {
+            context.signer_account_id = sponser_id.clone();
+            context.predecessor_account_id = sponser_id;
+            context.attached_deposit = amount + STORAGE_DEPOSIT;
+            testing_env!(context.clone());
+            contract.sponse_native(event_id.clone(), U128(amount), None);
+        }
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context);
+        contract.transfer_sponsorship(event_id, accounts(4), None);
+    }
+
+    #[test]
+    #[should_panic(
+        expected = "Sponsorships can't be transferred while a vote is open on the event"
+    )]
+    fn test_transfer_sponsorship_during_cancellation_vote() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.internal_register_account(&accounts(4));
+
+        for (sponser_id, amount) in [(accounts(5), 3_000), (accounts(3), 1_000)] {
+            context.signer_account_id = sponser_id.clone();
+            context.predecessor_account_id = sponser_id;
+            context.attached_deposit = amount + STORAGE_DEPOSIT;
+            testing_env!(context.clone());
+            contract.sponse_native(event_id.clone(), U128(amount), None);
+        }
+
+        // The stake votes, then tries to move to an account that could vote again
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context);
+        contract.open_cancellation_vote(event_id.clone());
+        contract.vote_cancellation(event_id.clone());
+        contract.transfer_sponsorship(event_id, accounts(4), None);
+    }
+
+    #[test]
+    fn test_sponsorship_limits() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        let no_limits = TokenLimits {
+            min_contribution: None,
+            max_contribution: None,
+            hard_cap: None,
+        };
+        contract.set_sponsorship_limits(
+            event_id.clone(),
+            Some(SponsorshipLimits {
+                allowlist_only: true,
+                near: TokenLimits {
+                    min_contribution: Some(U128(100)),
+                    max_contribution: Some(U128(1_000)),
+                    hard_cap: Some(U128(1_500)),
+                },
+                usdt: no_limits,
+            }),
+        );
+        contract.add_sponsor_allowlist(event_id.clone(), vec![accounts(3), accounts(4)]);
+        assert!(!contract.is_sponsor_allowed(event_id.clone(), accounts(5)));
+
+        // the first sponsor is capped by the maximum, the second by the hard cap
+        for (sponser_id, amount) in [(accounts(3), 1_200), (accounts(4), 800)] {
+            context.signer_account_id = sponser_id.clone();
+            context.predecessor_account_id = sponser_id;
+            context.attached_deposit = amount + STORAGE_DEPOSIT;
+            testing_env!(context.clone());
+            contract.sponse_native(event_id.clone(), U128(amount), None);
+        }
+        assert_eq!(
+            contract
+                .internal_unwrap_balance(&accounts(3), &event_id)
+                .unwrap()
+                .token_near,
+            1_000
+        );
+        assert_eq!(
+            contract
+                .internal_unwrap_balance(&accounts(4), &event_id)
+                .unwrap()
+                .token_near,
+            500
+        );
+        assert_eq!(contract.get_total_token_event(&event_id).token_near, 1_500);
+    }
+
+    /// Creates an event open to accounts(3) only, with the same limits in NEAR and USDT
+    fn limited_event(contract: &mut Contract, event_id: &str, limits: TokenLimits) {
+        contract.create_event(event_id.to_string(), String::from("Panana"));
+        contract.set_sponsorship_limits(
+            event_id.to_string(),
+            Some(SponsorshipLimits {
+                allowlist_only: true,
+                near: limits.clone(),
+                usdt: limits,
+            }),
+        );
+        contract.add_sponsor_allowlist(event_id.to_string(), vec![accounts(3)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "You are not on the allowlist of this event")]
+    fn test_sponse_without_being_allowlisted() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        let no_limits = TokenLimits {
+            min_contribution: None,
+            max_contribution: None,
+            hard_cap: None,
+        };
+        limited_event(&mut contract, &event_id, no_limits);
+
+        context.signer_account_id = accounts(4);
+        context.predecessor_account_id = accounts(4);
+        context.attached_deposit = 500 + STORAGE_DEPOSIT;
+        testing_env!(context);
+        contract.sponse_native(event_id, U128(500), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The contribution is below the minimum of the event")]
+    fn test_sponse_below_minimum() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        let limits = TokenLimits {
+            min_contribution: Some(U128(100)),
+            max_contribution: None,
+            hard_cap: None,
+        };
+        limited_event(&mut contract, &event_id, limits);
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 50 + STORAGE_DEPOSIT;
+        testing_env!(context);
+        contract.sponse_native(event_id, U128(50), None);
+    }
+
+    #[test]
+    fn test_ft_on_transfer_refunds_above_hard_cap() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        let limits = TokenLimits {
+            min_contribution: None,
+            max_contribution: None,
+            hard_cap: Some(U128(1_500_000)),
+        };
+        limited_event(&mut contract, &event_id, limits);
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = crate::USDT_TOKEN_ID.parse().unwrap();
+        testing_env!(context);
+        let refund = contract.ft_on_transfer(accounts(3), U128(2_000_000), event_id.clone());
+        match refund {
+            PromiseOrValue::Value(refund) => assert_eq!(refund, U128(500_000)),
+            PromiseOrValue::Promise(_) => panic!("The refund should be a value"),
+        }
+        assert_eq!(
+            contract.get_total_token_event(&event_id).token_usdt,
+            1_500_000
+        );
+    }
+
+    #[test]
+    fn test_event_roles() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.grant_event_role(event_id.clone(), accounts(3), EventRole::Editor);
+        contract.grant_event_role(event_id.clone(), accounts(4), EventRole::Admin);
+
+        // editors can update the event but not cancel it
+        context.signer_account_id = accounts(3);
+        testing_env!(context.clone());
+        assert_eq!(
+            contract
+                .update_event(event_id.clone(), String::from("Banana"))
+                .name,
+            "Banana"
+        );
+        assert!(!contract.check_event_role(&event_id, EventRole::Admin));
+
+        context.signer_account_id = accounts(4);
+        testing_env!(context.clone());
+        assert!(contract.check_event_role(&event_id, EventRole::Finance));
+        contract.cancel_events(event_id.clone());
+
+        // two-step transfer of the event to the editor
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        contract.propose_event_owner(event_id.clone(), Some(accounts(3)));
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        testing_env!(context);
+        contract.accept_event_ownership(event_id.clone());
+        assert_eq!(contract.watch_detail_event(&event_id).owner, accounts(3));
+        assert_eq!(contract.get_pending_event_owner(event_id.clone()), None);
+        assert!(!contract
+            .get_event_managers(event_id.clone())
+            .contains_key(&accounts(3)));
+        assert_eq!(
+            contract.get_all_event_client(),
+            vec![(event_id, String::from("Banana"))]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "You are not allowed to cancel")]
+    fn test_cancel_event_as_editor() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.grant_event_role(event_id.clone(), accounts(3), EventRole::Editor);
+
+        context.signer_account_id = accounts(3);
+        testing_env!(context);
+        contract.cancel_events(event_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "You haven't been proposed as the owner of this event")]
+    fn test_accept_event_ownership_without_proposal() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.propose_event_owner(event_id.clone(), Some(accounts(3)));
+
+        context.signer_account_id = accounts(4);
+        context.predecessor_account_id = accounts(4);
+        testing_env!(context);
+        contract.accept_event_ownership(event_id);
+    }
+
+    #[test]
+    #[should_panic(expected = "You are not allowed to settle the fees of this event")]
+    fn test_settle_event_fees_without_finance_role() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.set_platform_fee(1_000);
+
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        testing_env!(context.clone());
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.grant_event_role(event_id.clone(), accounts(4), EventRole::Editor);
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(1_000), None);
+
+        // accounts(1) owns the contract and finishes the event
+        context.signer_account_id = accounts(1);
+        context.predecessor_account_id = accounts(1);
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        contract.finish_event(event_id.clone());
+        assert_eq!(
+            contract.watch_detail_event(&event_id).status,
+            crate::event::Status::Finish
+        );
+
+        context.signer_account_id = accounts(4);
+        context.predecessor_account_id = accounts(4);
+        testing_env!(context);
+        contract.settle_event_fees(event_id);
+    }
+
+    #[test]
+    fn test_contribution_ledger() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        context.block_index = 7;
+        testing_env!(context.clone());
+        contract.sponse_native(
+            event_id.clone(),
+            U128(1_000),
+            Some(String::from("Good luck")),
+        );
+        context.attached_deposit = 500 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.more_sponse_native(event_id.clone(), U128(500), None);
+
+        context.signer_account_id = accounts(2);
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        contract.cancel_events(event_id.clone());
+        context.signer_account_id = accounts(3);
+        testing_env!(context);
+        contract.claim(&event_id);
+
+        assert_eq!(contract.get_event_contribution_count(event_id.clone()), 3);
+        let contributions = contract.get_event_contributions(event_id.clone(), None, None);
+        assert_eq!(contributions[0].kind, ContributionKind::Initial);
+        assert_eq!(contributions[0].memo.as_deref(), Some("Good luck"));
+        assert_eq!(contributions[0].block_height, U64(7));
+        assert_eq!(contributions[1].kind, ContributionKind::TopUp);
+        assert_eq!(contributions[1].amount, U128(500));
+        assert_eq!(contributions[2].kind, ContributionKind::Refund);
+        assert_eq!(contributions[2].amount, U128(1_500));
+
+        let page = contract.get_sponsor_contributions(accounts(3), Some(1), Some(1));
+        assert_eq!(page, vec![contributions[1].clone()]);
+    }
+
+    #[test]
+    #[should_panic(expected = "The memo should be at most 256 bytes")]
+    fn test_sponse_with_long_memo() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context);
+        contract.sponse_native(event_id, U128(1_000), Some("a".repeat(257)));
+    }
+
+    #[test]
+    #[should_panic(expected = "Must attach")]
+    fn test_sponse_native_without_storage_deposit() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000;
+        testing_env!(context);
+        contract.sponse_native(event_id, U128(1_000), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "The contribution should be at least 1 USDT")]
+    fn test_ft_on_transfer_below_min_contribution() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = crate::USDT_TOKEN_ID.parse().unwrap();
+        testing_env!(context);
+        contract.ft_on_transfer(accounts(3), U128(999_999), event_id);
+    }
+
+    #[test]
+    fn test_event_analytics() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let first_event = String::from("001");
+        let second_event = String::from("002");
+        contract.create_event(first_event.clone(), String::from("Panana"));
+        contract.create_event(second_event.clone(), String::from("Banana"));
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        context.block_timestamp = 42;
+        testing_env!(context.clone());
+        contract.sponse_native(first_event.clone(), U128(1_000), None);
+        context.attached_deposit = 300 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(second_event.clone(), U128(300), None);
+
+        context.signer_account_id = accounts(4);
+        context.predecessor_account_id = accounts(4);
+        context.attached_deposit = 500 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(second_event.clone(), U128(500), None);
+        context.block_timestamp = 84;
+        testing_env!(context.clone());
+        contract.more_sponse_native(second_event.clone(), U128(500), None);
+
+        context.signer_account_id = accounts(2);
+        context.attached_deposit = 1;
+        testing_env!(context);
+        contract.cancel_events(first_event.clone());
+
+        let stats = contract.get_platform_stats();
+        assert_eq!(stats.events_active, 1);
+        assert_eq!(stats.events_cancelled, 1);
+        assert_eq!(stats.unique_sponsors, 2);
+        assert_eq!(stats.total_near, U128(2_300));
+
+        assert_eq!(
+            contract.get_largest_events(Token::NEAR, None, None),
+            vec![
+                (second_event.clone(), U128(1_300)),
+                (first_event.clone(), U128(1_000))
+            ]
+        );
+        assert_eq!(
+            contract.get_largest_events(Token::NEAR, Some(1), Some(1)),
+            vec![(first_event, U128(1_000))]
+        );
+
+        let owner_stats = contract.get_owner_stats(accounts(2));
+        assert_eq!(owner_stats.events, 2);
+        assert_eq!(owner_stats.total_near, U128(2_300));
+
+        let event_stats = contract.get_event_stats(second_event);
+        assert_eq!(event_stats.sponsors, 2);
+        assert_eq!(event_stats.contributions, 3);
+        assert_eq!(event_stats.average_near, U128(650));
+        assert_eq!(event_stats.last_contribution_at, Some(U64(84)));
+    }
+
+    #[test]
+    fn test_event_stats_without_sponsors() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context);
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.create_event(String::from("001"), String::from("Panana"));
+        let stats = contract.get_event_stats(String::from("001"));
+        assert_eq!(stats.sponsors, 0);
+        assert_eq!(stats.average_near, U128(0));
+        assert_eq!(stats.average_usdt, U128(0));
+    }
+
+    #[test]
+    fn test_sponsor_leaderboards() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let first_event = String::from("001");
+        let second_event = String::from("002");
+        contract.create_event(first_event.clone(), String::from("Panana"));
+        contract.create_event(second_event.clone(), String::from("Banana"));
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(first_event.clone(), U128(1_000), None);
+
+        context.signer_account_id = accounts(4);
+        context.predecessor_account_id = accounts(4);
+        context.attached_deposit = 500 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(first_event.clone(), U128(500), None);
+        context.attached_deposit = 700 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.more_sponse_native(first_event.clone(), U128(700), None);
+        context.attached_deposit = 300 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(second_event.clone(), U128(300), None);
+
+        let ranked = |entries: Vec<LeaderboardEntry>| {
+            entries
+                .into_iter()
+                .map(|entry| (entry.sponser_id, entry.value))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            ranked(contract.get_event_leaderboard(first_event.clone(), Ranking::NEAR, None, None)),
+            vec![(accounts(4), U128(1_200)), (accounts(3), U128(1_000))]
+        );
+        assert_eq!(
+            ranked(contract.get_event_leaderboard(
+                first_event.clone(),
+                Ranking::NEAR,
+                Some(1),
+                Some(1)
+            )),
+            vec![(accounts(3), U128(1_000))]
+        );
+        assert!(contract
+            .get_event_leaderboard(first_event.clone(), Ranking::USDT, None, None)
+            .is_empty());
+        let global = contract.get_global_leaderboard(Token::NEAR, None, None);
+        assert_eq!(
+            ranked(global),
+            vec![(accounts(4), U128(1_500)), (accounts(3), U128(1_000))]
+        );
+
+        // Normalized in USDT, 1 NEAR = 3 USDT
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        contract.set_sponsor_tiers(first_event.clone(), Token::USDT, U128(3), U128(1), vec![]);
+        let normalized =
+            contract.get_event_leaderboard(first_event.clone(), Ranking::Normalized, None, None);
+        assert_eq!(normalized[0].value, U128(3_600));
+        assert_eq!(normalized[0].near, U128(1_200));
+        assert_eq!(normalized[1].value, U128(3_000));
+
+        context.attached_deposit = 1;
+        testing_env!(context.clone());
+        contract.cancel_events(first_event.clone());
+        context.signer_account_id = accounts(4);
+        context.predecessor_account_id = accounts(4);
+        testing_env!(context);
+        contract.claim(&first_event);
+
+        assert_eq!(
+            ranked(contract.get_event_leaderboard(first_event, Ranking::Normalized, None, None)),
+            vec![(accounts(3), U128(3_000))]
+        );
+        assert_eq!(
+            ranked(contract.get_global_leaderboard(Token::NEAR, None, None)),
+            vec![(accounts(3), U128(1_000)), (accounts(4), U128(300))]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The event has no conversion rate, set its tiers first")]
+    fn test_normalized_leaderboard_without_tiers() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context);
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.get_event_leaderboard(event_id, Ranking::Normalized, None, None);
+    }
+
+    /// Local stand-in for the price oracle: NEAR at `near_price` cents, USDT at 1 USD, with
+    /// the decimals the oracle uses (those of the token + 4).
+    fn mock_price_oracle(timestamp: u64, near_price: u128) -> Vec<u8> {
+        let price_data = PriceData {
+            timestamp: U64(timestamp),
+            recency_duration_sec: 90,
+            prices: vec![
+                AssetOptionalPrice {
+                    asset_id: String::from("wrap.near"),
+                    price: Some(Price {
+                        multiplier: U128(near_price * 100),
+                        decimals: 28,
+                    }),
+                },
+                AssetOptionalPrice {
+                    asset_id: String::from("usdt.near"),
+                    price: Some(Price {
+                        multiplier: U128(10_000),
+                        decimals: 10,
+                    }),
+                },
+            ],
+        };
+        near_sdk::serde_json::to_vec(&price_data).unwrap()
+    }
+
+    #[test]
+    fn test_usd_valuation() {
+        let mut context = get_context();
+        context.attached_deposit = 10u128.pow(24);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+        contract.set_price_oracle(Some(PriceOracleConfig {
+            oracle_id: accounts(5),
+            near_asset_id: String::from("wrap.near"),
+            usdt_asset_id: String::from("usdt.near"),
+            max_staleness: U64(1_000),
+        }));
+        let no_limits = TokenLimits {
+            min_contribution: None,
+            max_contribution: None,
+            hard_cap: None,
+        };
+        contract.set_sponsorship_limits(
+            event_id.clone(),
+            Some(SponsorshipLimits {
+                allowlist_only: false,
+                near: TokenLimits {
+                    hard_cap: Some(U128(10 * 10u128.pow(24))),
+                    ..no_limits.clone()
+                },
+                usdt: no_limits,
+            }),
+        );
+        contract.set_sponsor_tiers(
+            event_id.clone(),
+            Token::NEAR,
+            U128(1),
+            U128(1),
+            vec![SponsorTier {
+                name: String::from("Gold"),
+                min_contribution: U128(10u128.pow(24)),
+                perks_uri: String::from("ipfs://gold"),
+            }],
+        );
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 2 * 10u128.pow(24) + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(2 * 10u128.pow(24)), None);
+
+        // The oracle answers with NEAR at 3.10 USD
+        context.predecessor_account_id = accounts(0);
+        context.block_timestamp = 5_000;
+        testing_env!(context.clone());
+        contract.refresh_prices();
+        testing_env!(
+            context.clone(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(mock_price_oracle(4_500, 310))],
+        );
+        contract.price_data_callback();
+        assert_eq!(
+            contract.get_token_price(Token::NEAR).unwrap().timestamp,
+            U64(4_500)
+        );
+
+        // Prices older than the staleness limit are not cached
+        testing_env!(
+            context.clone(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(mock_price_oracle(3_000, 500))],
+        );
+        contract.price_data_callback();
+        assert_eq!(
+            contract.get_token_price(Token::NEAR).unwrap().multiplier,
+            U128(31_000)
+        );
+
+        assert_eq!(
+            contract.get_event_totals_usd(event_id.clone()),
+            UsdValue {
+                near: U128(6_200_000),
+                usdt: U128(0),
+                total: U128(6_200_000),
+            }
+        );
+        assert_eq!(
+            contract
+                .get_event_goals_usd(event_id.clone())
+                .unwrap()
+                .total,
+            U128(31_000_000)
+        );
+        assert_eq!(
+            contract.get_sponsor_tiers_usd(event_id),
+            vec![(String::from("Gold"), U128(3_100_000))]
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "The cached price is stale, call refresh_prices")]
+    fn test_usd_valuation_with_stale_price() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        let event_id = String::from("001");
+        contract.create_event(event_id.clone(), String::from("Panana"));
+
+        context.signer_account_id = accounts(3);
+        context.predecessor_account_id = accounts(3);
+        context.attached_deposit = 1_000 + STORAGE_DEPOSIT;
+        testing_env!(context.clone());
+        contract.sponse_native(event_id.clone(), U128(1_000), None);
+
+        context.predecessor_account_id = accounts(2);
+        context.block_timestamp = 5_000;
+        testing_env!(
+            context.clone(),
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(mock_price_oracle(4_500, 310))],
+        );
+        contract.set_price_oracle(Some(PriceOracleConfig {
+            oracle_id: accounts(5),
+            near_asset_id: String::from("wrap.near"),
+            usdt_asset_id: String::from("usdt.near"),
+            max_staleness: U64(1_000),
+        }));
+        contract.price_data_callback();
+
+        // The price was published at 4_500 and can be used until 5_500
+        context.block_timestamp = 5_501;
+        testing_env!(context);
+        contract.get_event_totals_usd(event_id);
+    }
+
+    #[test]
+    fn test_price_with_too_many_decimals() {
+        let mut context = get_context();
+        context.attached_deposit = 1;
+        context.signer_account_id = accounts(2);
+        context.predecessor_account_id = accounts(2);
+        context.block_timestamp = 5_000;
+        testing_env!(context.clone());
+        let mut contract = Contract::new_default_meta(U128(1_000));
+        contract.set_price_oracle(Some(PriceOracleConfig {
+            oracle_id: accounts(5),
+            near_asset_id: String::from("wrap.near"),
+            usdt_asset_id: String::from("usdt.near"),
+            max_staleness: U64(u64::MAX),
+        }));
+
+        let price_data = PriceData {
+            timestamp: U64(4_500),
+            recency_duration_sec: 90,
+            prices: vec![
+                AssetOptionalPrice {
+                    asset_id: String::from("wrap.near"),
+                    price: Some(Price {
+                        multiplier: U128(310),
+                        decimals: 45,
+                    }),
+                },
+                AssetOptionalPrice {
+                    asset_id: String::from("usdt.near"),
+                    price: Some(Price {
+                        multiplier: U128(10_000),
+                        decimals: 10,
+                    }),
+                },
+            ],
+        };
+        testing_env!(
+            context,
+            near_sdk::VMConfig::test(),
+            near_sdk::RuntimeFeesConfig::test(),
+            Default::default(),
+            vec![PromiseResult::Successful(
+                near_sdk::serde_json::to_vec(&price_data).unwrap()
+            )],
+        );
+        contract.price_data_callback();
+        assert!(contract.get_token_price(Token::NEAR).is_none());
+        assert_eq!(
+            contract.get_token_price(Token::USDT).unwrap().timestamp,
+            U64(4_500)
+        );
+    }
 }
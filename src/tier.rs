@@ -0,0 +1,153 @@
+use crate::*;
+
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct SponsorTier {
+    /// Gold, Silver, Bronze...
+    pub name: String,
+    /// Minimum contribution, in the reference token of the event
+    pub min_contribution: U128,
+    /// Metadata of the perks of the tier
+    pub perks_uri: String,
+}
+
+/// Tiers of the sponsors of an event. Contributions in the other token are converted with
+/// the rate `rate_other` of the other token = `rate_reference` of the reference token.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct EventTiers {
+    pub reference_token: Token,
+    pub rate_reference: U128,
+    pub rate_other: U128,
+    /// Tiers from the highest minimum contribution to the lowest
+    pub tiers: Vec<SponsorTier>,
+}
+
+impl EventTiers {
+    /// Contribution converted in the reference token.
+    pub fn normalize(&self, amount: &Amount) -> Balance {
+        let (reference, other) = match self.reference_token {
+            Token::NEAR => (amount.token_near, amount.token_usdt),
+            _ => (amount.token_usdt, amount.token_near),
+        };
+        reference + other * self.rate_reference.0 / self.rate_other.0
+    }
+
+    /// Index of the tier reached by the contribution, 0 being the highest.
+    pub fn tier_index(&self, amount: &Amount) -> Option<usize> {
+        let contribution = self.normalize(amount);
+        self.tiers
+            .iter()
+            .position(|tier| contribution >= tier.min_contribution.0)
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Defines the sponsor tiers of the event, replacing the previous ones.
+    /// The attached deposit covers the storage of the tiers, the rest is refunded.
+    #[payable]
+    pub fn set_sponsor_tiers(
+        &mut self,
+        event_id: EventId,
+        reference_token: Token,
+        rate_reference: U128,
+        rate_other: U128,
+        tiers: Vec<SponsorTier>,
+    ) {
+        let init_storage = env::storage_usage();
+        let mut tiers = tiers;
+        self.internal_watch_detail_event(&event_id);
+        require!(
+            self.check_owner_event(&event_id),
+            "Only the owner of the event can set its tiers"
+        );
+        require!(
+            reference_token != Token::PLAT,
+            "The reference token should be NEAR or USDT"
+        );
+        require!(
+            rate_reference.0 > 0 && rate_other.0 > 0,
+            "The conversion rate should be positive"
+        );
+        tiers.sort_by_key(|tier| std::cmp::Reverse(tier.min_contribution));
+
+        self.event_tiers.insert(
+            &event_id,
+            &EventTiers {
+                reference_token,
+                rate_reference,
+                rate_other,
+                tiers,
+            },
+        );
+        self.internal_rebuild_normalized_leaderboard(&event_id);
+        refund_deposit(init_storage);
+    }
+
+    pub fn get_sponsor_tiers(&self, event_id: EventId) -> Option<EventTiers> {
+        self.event_tiers.get(&event_id)
+    }
+
+    /// Returns the tier the sponsorship of `account_id` reaches, if any.
+    pub fn get_sponsor_tier(
+        &self,
+        event_id: EventId,
+        account_id: AccountId,
+    ) -> Option<SponsorTier> {
+        let event_tiers = self.event_tiers.get(&event_id)?;
+        let amount = self.internal_unwrap_balance(&account_id, &event_id).ok()?;
+        event_tiers
+            .tier_index(&amount)
+            .map(|index| event_tiers.tiers[index].clone())
+    }
+
+    /// Returns the sponsors of the event with their tier.
+    pub fn get_event_sponsor_tiers(&self, event_id: EventId) -> Vec<(AccountId, Option<String>)> {
+        let event = self.internal_watch_detail_event(&event_id);
+        event
+            .sponsers
+            .into_iter()
+            .map(|sponser_id| {
+                let tier = self
+                    .get_sponsor_tier(event_id.clone(), sponser_id.clone())
+                    .map(|tier| tier.name);
+                (sponser_id, tier)
+            })
+            .collect()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_tier_index(
+        &self,
+        account_id: &AccountId,
+        event_id: &EventId,
+    ) -> Option<usize> {
+        let event_tiers = self.event_tiers.get(event_id)?;
+        let amount = self.internal_unwrap_balance(account_id, event_id).ok()?;
+        event_tiers.tier_index(&amount)
+    }
+
+    /// Emits a tier change event if the sponsor is no longer in the tier `old_index`, whether
+    /// they reached a higher one or fell to a lower one.
+    pub(crate) fn internal_check_tier_change(
+        &self,
+        account_id: &AccountId,
+        event_id: &EventId,
+        old_index: Option<usize>,
+    ) {
+        let new_index = self.internal_tier_index(account_id, event_id);
+        if new_index == old_index {
+            return;
+        }
+        let tiers = self.event_tiers.get(event_id).unwrap().tiers;
+        SponsorTierChange {
+            event_id,
+            sponser_id: account_id,
+            old_tier: old_index.map(|index| tiers[index].name.as_str()),
+            new_tier: new_index.map(|index| tiers[index].name.as_str()),
+        }
+        .emit();
+    }
+}
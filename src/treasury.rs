@@ -0,0 +1,152 @@
+use crate::external::ext_self;
+use crate::*;
+
+#[near_bindgen]
+impl Contract {
+    /// Sets the account receiving the platform's share of sponsorships and unclaimed refunds.
+    /// It is also the treasurer allowed to sweep them.
+    pub fn set_treasury(&mut self, treasury_id: AccountId) {
+        self.assert_owner();
+        self.treasury_id = treasury_id;
+    }
+
+    pub fn get_treasury(&self) -> AccountId {
+        self.treasury_id.clone()
+    }
+
+    /// Sets the date after which the sponsors of the event can no longer `claim` their refund.
+    /// Like the other sponsorship terms, it can only be changed before the first sponsorship.
+    pub fn set_claim_deadline(&mut self, event_id: EventId, deadline: Option<U64>) {
+        let event = self.internal_watch_detail_event(&event_id);
+        require!(
+            self.check_owner_event(&event_id),
+            "Only the owner of the event can set its claim deadline"
+        );
+        require!(
+            event.sponsers.is_empty(),
+            "The claim deadline can't be changed once the event is sponsored"
+        );
+        match deadline {
+            Some(deadline) => {
+                self.claim_deadlines.insert(&event_id, &deadline.0);
+            }
+            None => {
+                self.claim_deadlines.remove(&event_id);
+            }
+        }
+    }
+
+    /// Moves the refunds that at most `limit` sponsors of a cancelled event didn't claim before
+    /// its deadline to the treasury, along with the fees they would have got back. Each swept
+    /// sponsorship is logged. Returns the swept NEAR and USDT.
+    pub fn sweep_unclaimed(&mut self, event_id: EventId, limit: Option<u64>) -> (U128, U128) {
+        let caller = env::predecessor_account_id();
+        require!(
+            caller == self.owner_id || caller == self.treasury_id,
+            "Only the owner or the treasurer can sweep unclaimed refunds"
+        );
+        let mut event = self.internal_watch_detail_event(&event_id);
+        require!(
+            event.status == Status::Cancel,
+            "The event has not been cancelled"
+        );
+        let deadline = self
+            .claim_deadlines
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic_str("The event has no claim deadline"));
+        require!(
+            env::block_timestamp() > deadline,
+            "The claim deadline is not over yet"
+        );
+
+        // Sponsors whose claim is in flight no longer have a balance and are skipped
+        let sponsers: Vec<(AccountId, Amount)> = event
+            .sponsers
+            .iter()
+            .filter_map(|sponser_id| {
+                self.internal_unwrap_balance(sponser_id, &event_id)
+                    .ok()
+                    .map(|amount| (sponser_id.clone(), amount))
+            })
+            .take(limit.unwrap_or(50) as usize)
+            .collect();
+        require!(!sponsers.is_empty(), "No unclaimed refunds to sweep");
+
+        let treasury_id = self.treasury_id.clone();
+        let (old_near, old_usdt) = (event.total_near, event.total_usdt);
+        let mut swept = vec![];
+        let (mut near, mut usdt) = (0, 0);
+        for (sponser_id, amount) in sponsers {
+            let refund_near = self.internal_refundable_amount(&event_id, amount.token_near);
+            let refund_usdt = self.internal_refundable_amount(&event_id, amount.token_usdt);
+            near += refund_near;
+            usdt += refund_usdt;
+            event.total_near -= refund_near;
+            event.total_usdt -= refund_usdt;
+            event.sponsers.retain(|item| *item != sponser_id);
+            self.handle_sponser_claim(sponser_id.clone(), event_id.clone());
+            self.internal_settle_sponser_fees(&sponser_id, &event_id);
+            // like on `claim`, the receipt stays as proof of the part that went to the event
+            if (refund_near, refund_usdt) == (amount.token_near, amount.token_usdt) {
+                self.internal_burn_receipt(&sponser_id, &event_id, "Refund swept to the treasury");
+            }
+            swept.push((sponser_id, U128(refund_near), U128(refund_usdt)));
+        }
+        self.events.insert(&event_id, &event);
+        self.internal_on_total_change(&event, &Token::NEAR, old_near);
+        self.internal_on_total_change(&event, &Token::USDT, old_usdt);
+
+        RefundSwept::emit_many(
+            &swept
+                .iter()
+                .map(|(sponser_id, near, usdt)| RefundSwept {
+                    event_id: &event_id,
+                    sponser_id,
+                    treasury_id: &treasury_id,
+                    near,
+                    usdt,
+                })
+                .collect::<Vec<_>>(),
+        );
+        for (token, amount) in [(Token::NEAR, near), (Token::USDT, usdt)] {
+            if amount > 0 {
+                self.internal_send_token(
+                    &token,
+                    &treasury_id,
+                    amount,
+                    Some(format!("Unclaimed refunds of event {}", event_id)),
+                )
+                .unwrap()
+                .then(
+                    ext_self::ext(env::current_account_id())
+                        .with_static_gas(FT_TRANSFER_GAS)
+                        .sweep_unclaimed_callback(token, amount.into()),
+                );
+            }
+        }
+        (near.into(), usdt.into())
+    }
+
+    pub fn get_claim_deadline(&self, event_id: EventId) -> Option<U64> {
+        self.claim_deadlines.get(&event_id).map(U64)
+    }
+}
+
+impl Contract {
+    /// Cancels the event. Its fee refund policy is fixed from now on, and the withdrawal
+    /// penalties it kept go to the treasury since no sponsor can claim them.
+    pub(crate) fn internal_cancel_event(&mut self, event: &mut Event) {
+        self.internal_set_event_status(event, Status::Cancel);
+        self.internal_record_fee_refund_policy(&event.id);
+        self.internal_settle_withdrawal_penalties(event);
+    }
+
+    pub(crate) fn assert_claim_open(&self, event_id: &EventId) {
+        if let Some(deadline) = self.claim_deadlines.get(event_id) {
+            require!(
+                env::block_timestamp() <= deadline,
+                "The claim deadline of this event is over"
+            );
+        }
+    }
+}
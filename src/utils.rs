@@ -16,9 +16,18 @@ pub(crate) fn assert_fee_storage_deposit() {
     )
 }
 
+pub(crate) fn assert_min_usdt_contribution(amount: Balance) {
+    assert!(
+        amount >= MIN_USDT_CONTRIBUTION,
+        "The contribution should be at least 1 USDT"
+    )
+}
+
 pub(crate) fn refund_deposit(init_storage: u64) {
     let finals_storage = env::storage_usage();
-    let required_cost = env::storage_byte_cost() * Balance::from(finals_storage - init_storage);
+    // Storage freed by the call (e.g. settled votes) is not charged
+    let required_cost =
+        env::storage_byte_cost() * Balance::from(finals_storage.saturating_sub(init_storage));
     let attached_deposit = env::attached_deposit();
 
     assert!(
@@ -33,3 +42,34 @@ pub(crate) fn refund_deposit(init_storage: u64) {
         Promise::new(env::predecessor_account_id()).transfer(refund);
     }
 }
+
+/// Refunds what is attached on top of `kept`, the part of the deposit the call keeps (e.g. a
+/// sponsorship), and of the storage the call used.
+pub(crate) fn refund_deposit_over(init_storage: u64, kept: Balance) {
+    let finals_storage = env::storage_usage();
+    let required_cost =
+        env::storage_byte_cost() * Balance::from(finals_storage.saturating_sub(init_storage));
+    let attached_deposit = env::attached_deposit();
+
+    assert!(
+        attached_deposit >= kept + required_cost,
+        "Must attach {} yoctoNear to cover the amount and {} storage",
+        kept + required_cost,
+        required_cost
+    );
+
+    let refund = attached_deposit - kept - required_cost;
+
+    if refund > 0 {
+        Promise::new(env::predecessor_account_id()).transfer(refund);
+    }
+}
+
+impl Contract {
+    pub(crate) fn assert_owner(&self) {
+        require!(
+            env::predecessor_account_id() == self.owner_id,
+            "Only the owner can call this method"
+        );
+    }
+}
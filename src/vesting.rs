@@ -0,0 +1,167 @@
+use near_sdk::json_types::U64;
+
+use crate::*;
+
+/// Cliff and linear vesting of a PLAT allocation. All timestamps and durations are in nanoseconds.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct VestingSchedule {
+    /// Total amount allocated to the beneficiary. Lowered to the vested amount on revocation.
+    pub total: U128,
+    /// Amount the beneficiary has already claimed.
+    pub claimed: U128,
+    /// Timestamp from which the allocation starts vesting.
+    pub start: U64,
+    /// Time after `start` before anything is vested.
+    pub cliff: U64,
+    /// Time after `start` at which the whole allocation is vested.
+    pub duration: U64,
+    /// Whether the owner can take back the unvested part.
+    pub revocable: bool,
+    pub revoked: bool,
+}
+
+impl VestingSchedule {
+    /// Amount vested at the given timestamp, claimed or not.
+    pub fn vested_amount(&self, timestamp: u64) -> Balance {
+        let total = self.total.0;
+        if self.revoked {
+            return total;
+        }
+        let elapsed = timestamp.saturating_sub(self.start.0);
+        if elapsed < self.cliff.0 {
+            0
+        } else if elapsed >= self.duration.0 {
+            total
+        } else {
+            // Split the multiplication so that `total * elapsed` can't overflow
+            let duration = self.duration.0 as u128;
+            let elapsed = elapsed as u128;
+            (total / duration) * elapsed + (total % duration) * elapsed / duration
+        }
+    }
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Creates the vesting schedule of `beneficiary_id`, locking `total` from the owner's balance.
+    /// The attached deposit covers the storage of the schedule, the rest is refunded.
+    #[payable]
+    pub fn create_vesting(
+        &mut self,
+        beneficiary_id: AccountId,
+        total: U128,
+        start: U64,
+        cliff: U64,
+        duration: U64,
+        revocable: bool,
+    ) -> VestingSchedule {
+        self.assert_owner();
+        let init_storage = env::storage_usage();
+        require!(total.0 > 0, "The amount should be a positive number");
+        require!(duration.0 > 0, "The vesting duration should be a positive number");
+        require!(
+            cliff.0 <= duration.0,
+            "The cliff must not be longer than the vesting duration"
+        );
+        require!(
+            !self.vestings.contains_key(&beneficiary_id),
+            "The beneficiary already has a vesting schedule"
+        );
+        // The beneficiary has to be registered to receive the vested tokens
+        self.internal_unwrap_balance_of(&beneficiary_id);
+
+        let schedule = VestingSchedule {
+            total,
+            claimed: U128(0),
+            start,
+            cliff,
+            duration,
+            revocable,
+            revoked: false,
+        };
+        self.vestings.insert(&beneficiary_id, &schedule);
+
+        let owner_id = self.owner_id.clone();
+        self.internal_lock(
+            &owner_id,
+            total.0,
+            Some(format!("Vesting for {}", beneficiary_id)),
+        );
+
+        refund_deposit(init_storage);
+        schedule
+    }
+
+    /// Transfers everything vested but not yet claimed to the caller. Returns the claimed amount.
+    pub fn claim_vested(&mut self) -> U128 {
+        let beneficiary_id = env::predecessor_account_id();
+        let mut schedule = self.internal_unwrap_vesting(&beneficiary_id);
+
+        let claimable = schedule.vested_amount(env::block_timestamp()) - schedule.claimed.0;
+        require!(claimable > 0, "Nothing has vested since the last claim");
+
+        schedule.claimed = U128(schedule.claimed.0 + claimable);
+        self.vestings.insert(&beneficiary_id, &schedule);
+        self.internal_unlock(
+            &beneficiary_id,
+            claimable,
+            Some("Vested tokens claimed".to_string()),
+        );
+        claimable.into()
+    }
+
+    /// Stops a revocable schedule and returns the unvested tokens to the owner.
+    /// What has vested so far stays claimable by the beneficiary. Returns the revoked amount.
+    pub fn revoke_vesting(&mut self, beneficiary_id: AccountId) -> U128 {
+        self.assert_owner();
+        let mut schedule = self.internal_unwrap_vesting(&beneficiary_id);
+        require!(schedule.revocable, "This vesting schedule is not revocable");
+        require!(!schedule.revoked, "This vesting schedule is already revoked");
+
+        let vested = schedule.vested_amount(env::block_timestamp());
+        let unvested = schedule.total.0 - vested;
+        schedule.total = U128(vested);
+        schedule.revoked = true;
+        self.vestings.insert(&beneficiary_id, &schedule);
+
+        if unvested > 0 {
+            let owner_id = self.owner_id.clone();
+            self.internal_unlock(
+                &owner_id,
+                unvested,
+                Some(format!("Vesting of {} revoked", beneficiary_id)),
+            );
+        }
+        unvested.into()
+    }
+
+    pub fn get_vesting(&self, account_id: AccountId) -> Option<VestingSchedule> {
+        self.vestings.get(&account_id)
+    }
+
+    /// Amount vested so far, including what was already claimed.
+    pub fn get_vested_amount(&self, account_id: AccountId) -> U128 {
+        self.internal_unwrap_vesting(&account_id)
+            .vested_amount(env::block_timestamp())
+            .into()
+    }
+
+    pub fn get_claimed_amount(&self, account_id: AccountId) -> U128 {
+        self.internal_unwrap_vesting(&account_id).claimed
+    }
+
+    /// Amount that has not vested yet.
+    pub fn get_locked_amount(&self, account_id: AccountId) -> U128 {
+        let schedule = self.internal_unwrap_vesting(&account_id);
+        (schedule.total.0 - schedule.vested_amount(env::block_timestamp())).into()
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_unwrap_vesting(&self, account_id: &AccountId) -> VestingSchedule {
+        self.vestings.get(account_id).unwrap_or_else(|| {
+            env::panic_str(format!("The account {} has no vesting schedule", account_id).as_str())
+        })
+    }
+}
@@ -0,0 +1,340 @@
+use crate::external::ext_self;
+use crate::prize::BPS_DENOMINATOR;
+use crate::*;
+use std::collections::HashMap;
+
+/// Who keeps the penalty of an early withdrawal.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub enum PenaltyReceiver {
+    /// The penalty stays in the funds of the event, where it doesn't count in the sponsors' votes.
+    /// No sponsor can get it back, so it goes to the treasury if the event is cancelled.
+    Event,
+    /// The penalty is credited to the treasury of the platform
+    Treasury,
+}
+
+/// Whether and how sponsors can take back their sponsorship while an event is active.
+#[derive(BorshDeserialize, BorshSerialize, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+#[serde(crate = "near_sdk::serde")]
+pub struct WithdrawalPolicy {
+    /// Time after their first deposit during which a sponsor can withdraw, in nanoseconds
+    pub window: U64,
+    /// Share of the withdrawn amount kept as a penalty, in basis points
+    pub penalty_bps: u16,
+    pub penalty_to: PenaltyReceiver,
+}
+
+#[near_bindgen]
+impl Contract {
+    /// Allows the sponsors of the event to withdraw early. Like the arbitrator, the policy can
+    /// only be changed before the first sponsorship.
+    pub fn set_withdrawal_policy(&mut self, event_id: EventId, policy: Option<WithdrawalPolicy>) {
+        let event = self.internal_watch_detail_event(&event_id);
+        require!(
+            self.check_owner_event(&event_id),
+            "Only the owner of the event can set its withdrawal policy"
+        );
+        require!(
+            event.sponsers.is_empty(),
+            "The withdrawal policy can't be changed once the event is sponsored"
+        );
+        match policy {
+            Some(policy) => {
+                require!(
+                    policy.penalty_bps as u128 <= BPS_DENOMINATOR,
+                    "The penalty should be at most 10000 basis points"
+                );
+                self.withdrawal_policies.insert(&event_id, &policy);
+            }
+            None => {
+                self.withdrawal_policies.remove(&event_id);
+            }
+        }
+    }
+
+    /// Takes back `amount` of the caller's sponsorship of an active event, within the withdrawal
+    /// window of the event. The penalty is deducted from the amount sent back, and is only
+    /// collected once the rest has been received.
+    /// The attached deposit covers the storage of the ledger entry, the rest is refunded.
+    #[payable]
+    pub fn withdraw_sponsorship(&mut self, event_id: EventId, token: Token, amount: U128) -> U128 {
+        let init_storage = env::storage_usage();
+        assert_at_least_one_yocto();
+        let sponser_id = env::predecessor_account_id();
+        let mut event = self.internal_watch_detail_event(&event_id);
+        require!(event.status == Status::Active, "The event is not active");
+        let policy = self
+            .withdrawal_policies
+            .get(&event_id)
+            .unwrap_or_else(|| env::panic_str("The event doesn't allow withdrawals"));
+        // Funds voted on through milestones can't be withdrawn
+        self.assert_sponsorship_open(&event_id);
+        let sponsored_at = self
+            .sponsored_at
+            .get(&(event_id.clone(), sponser_id.clone()))
+            .unwrap_or_else(|| env::panic_str("You haven't sponse this event before"));
+        require!(
+            env::block_timestamp() <= sponsored_at + policy.window.0,
+            "The withdrawal window is over"
+        );
+
+        let mut sponse_amount = self
+            .internal_unwrap_balance(&sponser_id, &event_id)
+            .unwrap_or_else(|_| env::panic_str("You haven't sponse this event before"));
+        let old_tier = self.internal_tier_index(&sponser_id, &event_id);
+        let old_amount = Amount {
+            token_near: sponse_amount.token_near,
+            token_usdt: sponse_amount.token_usdt,
+        };
+        let amount = amount.0;
+        require!(amount > 0, "The amount should be a positive number");
+        let balance = match token {
+            Token::NEAR => &mut sponse_amount.token_near,
+            Token::USDT => &mut sponse_amount.token_usdt,
+            Token::PLAT => env::panic_str("Token is invalid"),
+        };
+        *balance = balance
+            .checked_sub(amount)
+            .unwrap_or_else(|| env::panic_str("The amount exceeds your sponsorship"));
+        let penalty = amount * policy.penalty_bps as u128 / BPS_DENOMINATOR;
+        let payout = amount - penalty;
+
+        // Same bookkeeping as the claim callbacks, a sponsor with nothing left leaves the event
+        let withdrawn = if policy.penalty_to == PenaltyReceiver::Event {
+            payout
+        } else {
+            amount
+        };
+        match token {
+            Token::NEAR => event.total_near -= withdrawn,
+            _ => event.total_usdt -= withdrawn,
+        }
+        if sponse_amount.token_near == 0 && sponse_amount.token_usdt == 0 {
+            event.sponsers.retain(|item| *item != sponser_id);
+            self.handle_sponser_claim(sponser_id.clone(), event_id.clone());
+            self.sponsored_at
+                .remove(&(event_id.clone(), sponser_id.clone()));
+            self.internal_burn_receipt(&sponser_id, &event_id, "Sponsorship withdrawn");
+        } else {
+            let mut sponse = self.sponser_to_sponse.get(&sponser_id).unwrap();
+            sponse
+                .map_event_amount
+                .insert(event_id.clone(), sponse_amount);
+            self.sponser_to_sponse.insert(&sponser_id, &sponse);
+            self.internal_update_leaderboards(&sponser_id, &event_id, &old_amount);
+        }
+        self.events.insert(&event_id, &event);
+        self.internal_check_tier_change(&sponser_id, &event_id, old_tier);
+        let new_total = match token {
+            Token::NEAR => event.total_near,
+            _ => event.total_usdt,
+        };
+        self.internal_on_total_change(&event, &token, new_total + withdrawn);
+        self.internal_record_contribution(
+            &sponser_id,
+            &event_id,
+            &token,
+            amount,
+            ContributionKind::Withdrawal,
+            None,
+        );
+
+        if policy.penalty_to == PenaltyReceiver::Event {
+            let mut penalties = self.internal_withdrawal_penalties(&event_id);
+            match token {
+                Token::NEAR => penalties.token_near += penalty,
+                _ => penalties.token_usdt += penalty,
+            }
+            self.withdrawal_penalties.insert(&event_id, &penalties);
+        }
+
+        if payout > 0 {
+            self.internal_send_token(
+                &token,
+                &sponser_id,
+                payout,
+                Some(format!("Withdrawal from event {}", event_id)),
+            )
+            .unwrap()
+            .then(
+                ext_self::ext(env::current_account_id())
+                    .with_static_gas(FT_TRANSFER_GAS)
+                    .withdraw_sponsorship_callback(
+                        event_id.clone(),
+                        sponser_id,
+                        token,
+                        payout.into(),
+                        penalty.into(),
+                        sponsored_at.into(),
+                    ),
+            );
+        } else {
+            self.internal_collect_withdrawal_penalty(&event_id, &token, penalty);
+        }
+        refund_deposit(init_storage);
+        payout.into()
+    }
+
+    pub fn get_withdrawal_policy(&self, event_id: EventId) -> Option<WithdrawalPolicy> {
+        self.withdrawal_policies.get(&event_id)
+    }
+
+    /// Returns the withdrawal penalties kept in the funds of the event.
+    pub fn get_withdrawal_penalties(&self, event_id: EventId) -> Amount {
+        self.internal_withdrawal_penalties(&event_id)
+    }
+}
+
+impl Contract {
+    pub(crate) fn internal_withdrawal_penalties(&self, event_id: &EventId) -> Amount {
+        self.withdrawal_penalties.get(event_id).unwrap_or(Amount {
+            token_near: 0,
+            token_usdt: 0,
+        })
+    }
+
+    /// Funds of the event the votes of its sponsors are weighed against: the penalties it kept
+    /// belong to no sponsor.
+    pub(crate) fn internal_voting_totals(&self, event_id: &EventId) -> Amount {
+        let event = self.internal_watch_detail_event(event_id);
+        let penalties = self.internal_withdrawal_penalties(event_id);
+        Amount {
+            token_near: event.total_near - penalties.token_near,
+            token_usdt: event.total_usdt - penalties.token_usdt,
+        }
+    }
+
+    /// Credits the penalty of a withdrawal to the treasury when the policy of the event says so.
+    pub(crate) fn internal_collect_withdrawal_penalty(
+        &mut self,
+        event_id: &EventId,
+        token: &Token,
+        penalty: Balance,
+    ) {
+        if !self.internal_penalty_kept_by_event(event_id) {
+            self.internal_credit_treasury(token, penalty);
+        }
+    }
+
+    /// Moves the penalties kept in the funds of a cancelled event to the treasury. Like the
+    /// refunds, only what hasn't been released to the owner is moved.
+    pub(crate) fn internal_settle_withdrawal_penalties(&mut self, event: &mut Event) {
+        let penalties = match self.withdrawal_penalties.remove(&event.id) {
+            Some(penalties) => penalties,
+            None => return,
+        };
+        let near = self.internal_refundable_amount(&event.id, penalties.token_near);
+        let usdt = self.internal_refundable_amount(&event.id, penalties.token_usdt);
+        let (old_near, old_usdt) = (event.total_near, event.total_usdt);
+        event.total_near -= near;
+        event.total_usdt -= usdt;
+        self.internal_on_total_change(event, &Token::NEAR, old_near);
+        self.internal_on_total_change(event, &Token::USDT, old_usdt);
+        self.internal_credit_treasury(&Token::NEAR, near);
+        self.internal_credit_treasury(&Token::USDT, usdt);
+    }
+
+    /// Cancels a withdrawal whose payout couldn't be sent: the sponsor gets back the whole
+    /// withdrawn amount, penalty included, and keeps their place in the withdrawal window.
+    pub(crate) fn internal_cancel_withdrawal(
+        &mut self,
+        sponser_id: &AccountId,
+        event_id: &EventId,
+        token: &Token,
+        payout: Balance,
+        penalty: Balance,
+        sponsored_at: u64,
+    ) {
+        if self.internal_penalty_kept_by_event(event_id) {
+            // the penalty never left the event funds, it goes back to the sponsor with the rest
+            let mut penalties = self.internal_withdrawal_penalties(event_id);
+            let mut event = self.internal_watch_detail_event(event_id);
+            match token {
+                Token::NEAR => {
+                    penalties.token_near -= penalty;
+                    event.total_near -= penalty;
+                }
+                _ => {
+                    penalties.token_usdt -= penalty;
+                    event.total_usdt -= penalty;
+                }
+            }
+            self.withdrawal_penalties.insert(event_id, &penalties);
+            self.events.insert(event_id, &event);
+            let new_total = match token {
+                Token::NEAR => event.total_near,
+                _ => event.total_usdt,
+            };
+            self.internal_on_total_change(&event, token, new_total + penalty);
+        }
+        self.internal_restore_sponsorship(sponser_id, event_id, token, payout + penalty);
+        self.sponsored_at
+            .insert(&(event_id.clone(), sponser_id.clone()), &sponsored_at);
+    }
+
+    fn internal_penalty_kept_by_event(&self, event_id: &EventId) -> bool {
+        self.withdrawal_policies
+            .get(event_id)
+            .map(|policy| policy.penalty_to)
+            != Some(PenaltyReceiver::Treasury)
+    }
+
+    /// Gives back to a sponsor an amount of the event they couldn't receive.
+    pub(crate) fn internal_restore_sponsorship(
+        &mut self,
+        sponser_id: &AccountId,
+        event_id: &EventId,
+        token: &Token,
+        amount: Balance,
+    ) {
+        let old_tier = self.internal_tier_index(sponser_id, event_id);
+        let mut event = self.internal_watch_detail_event(event_id);
+        let old_amount = self
+            .internal_unwrap_balance(sponser_id, event_id)
+            .unwrap_or(Amount {
+                token_near: 0,
+                token_usdt: 0,
+            });
+        let mut sponse = self.sponser_to_sponse.get(sponser_id).unwrap_or(Sponse {
+            events: HashSet::new(),
+            map_event_amount: HashMap::new(),
+        });
+        let sponse_amount = sponse
+            .map_event_amount
+            .entry(event_id.clone())
+            .or_insert(Amount {
+                token_near: 0,
+                token_usdt: 0,
+            });
+        if *token == Token::NEAR {
+            sponse_amount.token_near += amount;
+            event.total_near += amount;
+        } else {
+            sponse_amount.token_usdt += amount;
+            event.total_usdt += amount;
+        }
+        sponse.events.insert(event_id.clone());
+        self.sponser_to_sponse.insert(sponser_id, &sponse);
+        self.internal_update_leaderboards(sponser_id, event_id, &old_amount);
+        if !event.sponsers.contains(sponser_id) {
+            event.sponsers.push(sponser_id.clone());
+            self.internal_mint_receipt(sponser_id, event_id);
+        }
+        self.events.insert(event_id, &event);
+        self.internal_check_tier_change(sponser_id, event_id, old_tier);
+        let new_total = match token {
+            Token::NEAR => event.total_near,
+            _ => event.total_usdt,
+        };
+        self.internal_on_total_change(&event, token, new_total - amount);
+        self.internal_record_contribution(
+            sponser_id,
+            event_id,
+            token,
+            amount,
+            ContributionKind::TopUp,
+            Some("Returned withdrawal".to_string()),
+        );
+    }
+}